@@ -0,0 +1,30 @@
+//! Build the runtime in `runtime/std.rs` into a static library, which the compiler bundles and links into executables.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=runtime/std.rs");
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    let rustc = std::env::var_os("RUSTC").unwrap();
+    let target = std::env::var("TARGET").unwrap();
+
+    let output = Command::new(rustc)
+        .args(["--crate-type", "staticlib", "--crate-name", "iranta_std", "--edition", "2024"])
+        .args(["-C", "opt-level=3", "--target", &target, "--print", "native-static-libs", "-o"])
+        .arg(out_dir.join("libiranta_std.a"))
+        .arg("runtime/std.rs")
+        .output()
+        .expect("Cannot run rustc to build the runtime.");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        panic!("Cannot build the runtime:\n{}", stderr);
+    }
+
+    // rustc names the system libraries a static library needs in a note, which are passed to the linker with it.
+    let native_libs = stderr.lines()
+        .find_map(|line| line.split_once("native-static-libs: ").map(|(_, libs)| libs.trim()))
+        .unwrap_or_default();
+    println!("cargo:rustc-env=IRANTA_STD_NATIVE_LIBS={}", native_libs);
+}
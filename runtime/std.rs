@@ -0,0 +1,110 @@
+//! The standard library of Iranta, called by the generated code to print values and to report failed checks.
+//!
+//! `build.rs` compiles this file into a static library bundled into the compiler and linked into every executable,
+//! and the JIT includes it as a module to run programs in process.
+
+use std::io::Write;
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_int8(value: i8) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_uint8(value: u8) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_int16(value: i16) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_uint16(value: u16) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_int32(value: i32) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_uint32(value: u32) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_int64(value: i64) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_uint64(value: u64) {
+    print!("{}", value);
+}
+
+/// 128-bit integers are passed by pointer, as their calling convention differs between C compilers.
+///
+/// # Safety
+/// `value` must point to a valid `i128`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn std_print_int128(value: *const i128) {
+    print!("{}", unsafe { *value });
+}
+
+/// # Safety
+/// `value` must point to a valid `u128`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn std_print_uint128(value: *const u128) {
+    print!("{}", unsafe { *value });
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_float32(value: f32) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_float64(value: f64) {
+    print!("{}", value);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_bool(value: u8) {
+    print!("{}", value != 0);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn std_print_char(value: u32) {
+    print!("{}", char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER));
+}
+
+/// # Safety
+/// `ptr` must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn std_print_str(ptr: *const u8, len: i64) {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, len as usize) };
+    print!("{}", String::from_utf8_lossy(bytes));
+}
+
+/// Print a new line and flush, as the program may exit through C without flushing the output of Rust.
+#[unsafe(no_mangle)]
+pub extern "C" fn std_println() {
+    println!();
+    let _ = std::io::stdout().flush();
+}
+
+/// Report a check that failed while the program runs, such as an arithmetic overflow, and exit with the code 101.
+///
+/// # Safety
+/// `msg` must point to `msg_len` readable bytes, and `file` to `file_len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn std_panic(msg: *const u8, msg_len: i64, file: *const u8, file_len: i64, line: i64, col: i64) -> ! {
+    let msg = unsafe { std::slice::from_raw_parts(msg, msg_len as usize) };
+    let file = unsafe { std::slice::from_raw_parts(file, file_len as usize) };
+    let _ = std::io::stdout().flush();
+    eprintln!("Panicked at {}:{}:{}: {}", String::from_utf8_lossy(file), line, col, String::from_utf8_lossy(msg));
+    std::process::exit(101);
+}
@@ -0,0 +1,516 @@
+//! The module of `BorrowChecker`, which checks that a borrow is not conflicted while a reference holding it is live
+
+use crate::analyzer::error::CompileError;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
+use crate::mir::{BlockId, Body, BodyKind, Local, Mir, Operand, Place, Rvalue, Statement, StatementKind, Storage, Terminator};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub struct BorrowChecker<'a> {
+    symbols: &'a SymbolInterner,
+    type_interner: &'a TypeInterner,
+    errors: Vec<CompileError>,
+}
+
+/// A loan held by a top-level variable of a REPL input, of the top-level variable `borrowed`, kept for later inputs.
+#[derive(Clone)]
+pub struct GlobalLoan {
+    pub borrowed: Symbol,
+    pub mutable: bool,
+    pub pos: Position,
+}
+
+type LoanId = usize;
+
+/// A borrow of a variable, made by a `Ref` statement or by an earlier REPL input at `pos`.
+struct Loan {
+    local: Local,
+    mutable: bool,
+    pos: Position,
+}
+
+/// The loans of a body, with the one made by the statement at each block and index and those held on entry.
+struct Loans {
+    list: Vec<Loan>,
+    made_at: HashMap<(BlockId, usize), LoanId>,
+    held_at_entry: HeldLoans,
+}
+
+/// The loans each local may hold at a point of the body.
+type HeldLoans = Vec<BTreeSet<LoanId>>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AccessKind {
+    Read,
+    Move,
+    Write,
+    BorrowShared,
+    BorrowMut,
+}
+
+impl<'a> BorrowChecker<'a> {
+    #[must_use]
+    pub fn new(symbols: &'a SymbolInterner, type_interner: &'a TypeInterner) -> Self {
+        Self {
+            symbols,
+            type_interner,
+            errors: vec![],
+        }
+    }
+
+    /// Check the MIR of a program, with `globals` the loans of earlier REPL inputs, updated to its end.
+    #[must_use]
+    pub fn check(mut self, mir: &Mir, mut globals: Option<&mut HashMap<Symbol, Vec<GlobalLoan>>>) -> Vec<CompileError> {
+        for body in &mir.bodies {
+            let no_globals = HashMap::new();
+            let loans = Self::collect_loans(body, globals.as_deref().unwrap_or(&no_globals));
+            let held_in = self.check_body(body, &loans);
+            if let (BodyKind::TopLevel, Some(globals)) = (body.kind, globals.as_deref_mut()) {
+                *globals = self.global_loans(body, &loans, &held_in);
+            }
+        }
+        self.errors
+    }
+
+    /// Check a body, giving back the loans each local may hold at the start of each block.
+    fn check_body(&mut self, body: &Body, loans: &Loans) -> Vec<Option<HeldLoans>> {
+        let live_out = Self::liveness(body);
+        let held_in = self.reaching_loans(body, loans);
+
+        let mut dead_loans = HashSet::new();
+        for (block_id, block) in body.blocks.iter().enumerate() {
+            let Some(mut held) = held_in[block_id].clone() else { continue };
+
+            // The locals live before each statement, worked out backwards from the end of the block.
+            let mut live = live_out[block_id].clone();
+            Self::transfer_terminator_liveness(body, &block.terminator, &mut live);
+            let live_at_terminator = live.clone();
+            let mut live_before = vec![BTreeSet::new(); block.stmts.len()];
+            for (stmt, stmt_live) in block.stmts.iter().zip(&mut live_before).rev() {
+                Self::transfer_liveness(stmt, &mut live);
+                stmt_live.clone_from(&live);
+            }
+
+            for (index, (stmt, live)) in block.stmts.iter().zip(&live_before).enumerate() {
+                let live_loans = Self::live_loans(&held, live);
+                // A loan the value of a function may hold is only live at the end of the body through that value.
+                let returned = if live.contains(&Local(0)) { held[0].clone() } else { BTreeSet::new() };
+                self.check_statement(body, loans, stmt, &live_loans, &returned, &mut dead_loans);
+                self.transfer_loans(body, loans, (BlockId(block_id), index), stmt, &mut held);
+            }
+
+            if let Terminator::SwitchBool { cond, pos, .. } = &block.terminator && let Some((local, kind)) = Self::operand_access(cond) {
+                let live_loans = Self::live_loans(&held, &live_at_terminator);
+                self.check_access(body, loans, local, kind, pos, &live_loans);
+            }
+        }
+        held_in
+    }
+
+    /// The loans the top-level variables of a REPL input hold at its end, of variables later inputs can still name.
+    fn global_loans(&self, body: &Body, loans: &Loans, held_in: &[Option<HeldLoans>]) -> HashMap<Symbol, Vec<GlobalLoan>> {
+        let Some(return_block) = body.blocks.iter().position(|block| matches!(block.terminator, Terminator::Return)) else {
+            return HashMap::new();
+        };
+        let Some(mut held) = held_in[return_block].clone() else { return HashMap::new() };
+        for (index, stmt) in body.blocks[return_block].stmts.iter().enumerate() {
+            self.transfer_loans(body, loans, (BlockId(return_block), index), stmt, &mut held);
+        }
+
+        // A variable shadowed by a later one can no longer be named, so the loans of it are dropped.
+        let mut latest = HashMap::new();
+        for (index, local) in body.locals.iter().enumerate() {
+            if local.storage != Storage::Stack {
+                latest.insert(local.name.unwrap(), Local(index));
+            }
+        }
+        latest.iter().map(|(name, local)| {
+            let global_loans = held[local.0].iter().map(|loan_id| &loans.list[*loan_id])
+                .filter_map(|loan| {
+                    let borrowed = body.locals[loan.local.0].name?;
+                    (latest.get(&borrowed) == Some(&loan.local)).then(|| GlobalLoan { borrowed, mutable: loan.mutable, pos: loan.pos.clone() })
+                })
+                .collect();
+            (*name, global_loans)
+        }).collect()
+    }
+
+    /// Number the loans made by the `Ref` statements of a body and those held by the variables of earlier REPL inputs.
+    fn collect_loans(body: &Body, globals: &HashMap<Symbol, Vec<GlobalLoan>>) -> Loans {
+        let mut loans = Loans {
+            list: vec![],
+            made_at: HashMap::new(),
+            held_at_entry: vec![BTreeSet::new(); body.locals.len()],
+        };
+        let extern_globals: HashMap<Symbol, Local> = body.locals.iter().enumerate()
+            .filter(|(_, local)| local.storage == Storage::ExternGlobal)
+            .map(|(index, local)| (local.name.unwrap(), Local(index)))
+            .collect();
+        for (name, local) in &extern_globals {
+            for global_loan in globals.get(name).into_iter().flatten() {
+                loans.held_at_entry[local.0].insert(loans.list.len());
+                let borrowed = extern_globals[&global_loan.borrowed];
+                loans.list.push(Loan { local: borrowed, mutable: global_loan.mutable, pos: global_loan.pos.clone() });
+            }
+        }
+        for (block_id, block) in body.blocks.iter().enumerate() {
+            for (index, stmt) in block.stmts.iter().enumerate() {
+                if let StatementKind::Assign(_, Rvalue::Ref { mutable, place }) = &stmt.kind {
+                    loans.made_at.insert((BlockId(block_id), index), loans.list.len());
+                    loans.list.push(Loan { local: place.local, mutable: *mutable, pos: stmt.pos.clone() });
+                }
+            }
+        }
+        loans
+    }
+
+    fn operand_access(operand: &Operand) -> Option<(Local, AccessKind)> {
+        match operand {
+            Operand::Copy(place) => Some((place.local, AccessKind::Read)),
+            Operand::Move(place) => Some((place.local, AccessKind::Move)),
+            Operand::Constant(_) => None,
+        }
+    }
+
+    /// The accesses to locals made by an assignment, operands first, where going through a reference reads its local.
+    fn accesses(place: &Place, rvalue: &Rvalue) -> Vec<(Local, AccessKind)> {
+        let operands: Vec<&Operand> = match rvalue {
+            Rvalue::Use(operand) | Rvalue::UnaryOp(_, operand) | Rvalue::Cast(operand, _) => vec![operand],
+            Rvalue::BinaryOp(_, lhs, rhs) => vec![lhs, rhs],
+            Rvalue::Call(_, args) => args.iter().collect(),
+            Rvalue::Ref { .. } => vec![],
+        };
+        let mut accesses: Vec<(Local, AccessKind)> = operands.into_iter().filter_map(Self::operand_access).collect();
+        if let Rvalue::Ref { mutable, place } = rvalue {
+            accesses.push((place.local, if *mutable { AccessKind::BorrowMut } else { AccessKind::BorrowShared }));
+        }
+        accesses.push((place.local, if place.deref { AccessKind::Read } else { AccessKind::Write }));
+
+        // A local used twice in the same way is only checked once.
+        let mut unique = vec![];
+        for access in accesses {
+            if !unique.contains(&access) {
+                unique.push(access);
+            }
+        }
+        unique
+    }
+
+    /// Work out the locals whose references may be used after the end of each block.
+    fn liveness(body: &Body) -> Vec<BTreeSet<Local>> {
+        let mut live_out = vec![BTreeSet::new(); body.blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (block_id, block) in body.blocks.iter().enumerate().rev() {
+                let mut live: BTreeSet<Local> = block.terminator.successors().into_iter()
+                    .flat_map(|succ| Self::live_in(body, &live_out, succ))
+                    .collect();
+                if live != live_out[block_id] {
+                    std::mem::swap(&mut live, &mut live_out[block_id]);
+                    changed = true;
+                }
+            }
+        }
+        live_out
+    }
+
+    fn live_in(body: &Body, live_out: &[BTreeSet<Local>], block_id: BlockId) -> BTreeSet<Local> {
+        let block = &body.blocks[block_id.0];
+        let mut live = live_out[block_id.0].clone();
+        Self::transfer_terminator_liveness(body, &block.terminator, &mut live);
+        for stmt in block.stmts.iter().rev() {
+            Self::transfer_liveness(stmt, &mut live);
+        }
+        live
+    }
+
+    /// Update the live locals from after a terminator to before it, where `Return` uses `_0` or the REPL globals.
+    fn transfer_terminator_liveness(body: &Body, terminator: &Terminator, live: &mut BTreeSet<Local>) {
+        match terminator {
+            Terminator::SwitchBool { cond, .. } => live.extend(Self::operand_access(cond).map(|(local, _)| local)),
+            Terminator::Return => match body.kind {
+                BodyKind::Fn(_) => {
+                    live.insert(Local(0));
+                }
+                BodyKind::TopLevel => {
+                    let globals = body.locals.iter().enumerate().filter(|(_, local)| local.storage != Storage::Stack);
+                    live.extend(globals.map(|(index, _)| Local(index)));
+                }
+            },
+            Terminator::Goto(_) | Terminator::Unreachable => (),
+        }
+    }
+
+    /// Update the live locals from after `stmt` to before it.
+    fn transfer_liveness(stmt: &Statement, live: &mut BTreeSet<Local>) {
+        match &stmt.kind {
+            StatementKind::Assign(place, rvalue) => {
+                let accesses = Self::accesses(place, rvalue);
+                for (local, kind) in &accesses {
+                    if *kind == AccessKind::Write {
+                        live.remove(local);
+                    }
+                }
+                live.extend(accesses.iter().filter(|(_, kind)| *kind != AccessKind::Write).map(|(local, _)| *local));
+            }
+            StatementKind::StorageDead(local) => {
+                live.remove(local);
+            }
+            StatementKind::PlaceMention(place) => {
+                live.insert(place.local);
+            }
+        }
+    }
+
+    /// Work out the loans each local may hold at the start of each block, `None` for a block that cannot be reached.
+    fn reaching_loans(&self, body: &Body, loans: &Loans) -> Vec<Option<HeldLoans>> {
+        let mut held_in: Vec<Option<HeldLoans>> = vec![None; body.blocks.len()];
+        held_in[0] = Some(loans.held_at_entry.clone());
+
+        let mut worklist = vec![0];
+        while let Some(block_id) = worklist.pop() {
+            let mut held = held_in[block_id].clone().unwrap();
+            for (index, stmt) in body.blocks[block_id].stmts.iter().enumerate() {
+                self.transfer_loans(body, loans, (BlockId(block_id), index), stmt, &mut held);
+            }
+            for succ in body.blocks[block_id].terminator.successors() {
+                match &mut held_in[succ.0] {
+                    succ_held @ None => {
+                        *succ_held = Some(held.clone());
+                        worklist.push(succ.0);
+                    }
+                    Some(succ_held) => {
+                        let mut changed = false;
+                        for (succ_loans, loans) in succ_held.iter_mut().zip(&held) {
+                            let len = succ_loans.len();
+                            succ_loans.extend(loans.iter().copied());
+                            changed |= succ_loans.len() != len;
+                        }
+                        if changed {
+                            worklist.push(succ.0);
+                        }
+                    }
+                }
+            }
+        }
+        held_in
+    }
+
+    /// Update the loans held by each local from before `stmt` to after it, where `at` is its block and index.
+    fn transfer_loans(&self, body: &Body, loans: &Loans, at: (BlockId, usize), stmt: &Statement, held: &mut HeldLoans) {
+        match &stmt.kind {
+            StatementKind::Assign(place, rvalue) if !place.deref => {
+                let mut new_held = BTreeSet::new();
+                // A value that is not a reference holds no loans, even if it is computed from references.
+                if self.is_ref(body.locals[place.local.0].type_id) {
+                    for (local, kind) in Self::accesses(place, rvalue) {
+                        if kind != AccessKind::Write {
+                            new_held.extend(held[local.0].iter().copied());
+                        }
+                    }
+                    new_held.extend(loans.made_at.get(&at).copied());
+                }
+                held[place.local.0] = new_held;
+            }
+            StatementKind::Assign(..) | StatementKind::PlaceMention(_) => (),
+            StatementKind::StorageDead(local) => {
+                held[local.0].clear();
+                // The loans of the variable that are still needed are reported here, and dropping them keeps a later
+                // iteration of a loop declaring the variable again from conflicting with them.
+                for local_held in held.iter_mut() {
+                    local_held.retain(|loan_id| loans.list[*loan_id].local != *local);
+                }
+            }
+        }
+    }
+
+    fn live_loans(held: &HeldLoans, live: &BTreeSet<Local>) -> BTreeSet<LoanId> {
+        live.iter().flat_map(|local| held[local.0].iter().copied()).collect()
+    }
+
+    fn is_ref(&self, type_id: TypeId) -> bool {
+        type_id != TypeId::NEVER && self.type_interner.ref_target(type_id).is_some()
+    }
+
+    fn local_name(&self, body: &Body, local: Local) -> &str {
+        self.symbols.get_name(body.locals[local.0].name.unwrap())
+    }
+
+    /// Add a note at each loan to an error.
+    fn with_loan_notes(&self, body: &Body, loans: &Loans, mut error: CompileError, loan_ids: impl IntoIterator<Item = LoanId>) -> CompileError {
+        for loan_id in loan_ids {
+            let loan = &loans.list[loan_id];
+            let name = self.local_name(body, loan.local);
+            let how = if loan.mutable { "mutably borrowed" } else { "borrowed" };
+            let note = if loan.pos == error.pos {
+                format!("'{}' is {} here, in a previous iteration of the loop.", name, how)
+            } else {
+                format!("'{}' is {} here.", name, how)
+            };
+            error = error.with_note(loan.pos.clone(), note);
+        }
+        error
+    }
+
+    /// Check a statement against the loans still needed after it, of which `returned` are held by the returned value.
+    fn check_statement(&mut self, body: &Body, loans: &Loans, stmt: &Statement, live_loans: &BTreeSet<LoanId>, returned: &BTreeSet<LoanId>, dead_loans: &mut HashSet<LoanId>) {
+        match &stmt.kind {
+            StatementKind::Assign(place, rvalue) => {
+                for (local, kind) in Self::accesses(place, rvalue) {
+                    self.check_access(body, loans, local, kind, &stmt.pos, live_loans);
+                }
+            }
+            StatementKind::PlaceMention(place) => self.check_access(body, loans, place.local, AccessKind::Read, &stmt.pos, live_loans),
+            StatementKind::StorageDead(local) => {
+                let dangling: Vec<LoanId> = live_loans.iter().copied()
+                    .filter(|loan_id| loans.list[*loan_id].local == *local && dead_loans.insert(*loan_id))
+                    .collect();
+                for loan_id in dangling {
+                    let name = self.local_name(body, *local);
+                    let msg = if returned.contains(&loan_id) {
+                        format!("Cannot return a reference to local variable '{}'.", name)
+                    } else {
+                        format!("'{}' does not live long enough.", name)
+                    };
+                    let note = format!("'{}' goes out of scope here while still borrowed.", name);
+                    let error = CompileError::new(loans.list[loan_id].pos.clone(), msg).with_note(stmt.pos.clone(), note);
+                    self.errors.push(error);
+                }
+            }
+        }
+    }
+
+    fn check_access(&mut self, body: &Body, loans: &Loans, local: Local, kind: AccessKind, pos: &Position, live_loans: &BTreeSet<LoanId>) {
+        let conflicts: Vec<LoanId> = live_loans.iter().copied().filter(|loan_id| {
+            let loan = &loans.list[*loan_id];
+            loan.local == local && (loan.mutable || !matches!(kind, AccessKind::Read | AccessKind::BorrowShared))
+        }).collect();
+        if conflicts.is_empty() {
+            return;
+        }
+
+        let name = self.local_name(body, local);
+        let msg = match kind {
+            AccessKind::Read => format!("Cannot use '{}' because it is mutably borrowed.", name),
+            AccessKind::Move => format!("Cannot move out of '{}' because it is borrowed.", name),
+            AccessKind::Write => format!("Cannot assign to '{}' because it is borrowed.", name),
+            AccessKind::BorrowShared => format!("Cannot borrow '{}' as shared because it is also borrowed as mutable.", name),
+            AccessKind::BorrowMut if conflicts.iter().any(|loan_id| loans.list[*loan_id].mutable) => {
+                format!("Cannot borrow '{}' as mutable more than once at a time.", name)
+            }
+            AccessKind::BorrowMut => format!("Cannot borrow '{}' as mutable because it is also borrowed as shared.", name),
+        };
+        let error = self.with_loan_notes(body, loans, CompileError::new(pos.clone(), msg), conflicts);
+        self.errors.push(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::tests::check_lines as check;
+
+    #[test]
+    fn use_while_mutably_borrowed() {
+        let errors = check("
+            let mut x: int32 = 1;
+            let r = &mut x;
+            let y = x;
+            *r = 2;
+        ");
+        assert_eq!(errors, [(4, "Cannot use 'x' because it is mutably borrowed.".to_string(), vec![3])]);
+    }
+
+    #[test]
+    fn mutable_borrow_while_shared() {
+        let errors = check("
+            let mut x: int32 = 1;
+            let r = &x;
+            let m = &mut x;
+            let y = *r;
+        ");
+        assert_eq!(errors, [(4, "Cannot borrow 'x' as mutable because it is also borrowed as shared.".to_string(), vec![3])]);
+    }
+
+    #[test]
+    fn two_mutable_borrows_in_one_call() {
+        let errors = check("
+            fn f(a: &mut int32, b: &mut int32) {}
+            let mut x: int32 = 1;
+            f(&mut x, &mut x);
+        ");
+        assert_eq!(errors, [(4, "Cannot borrow 'x' as mutable more than once at a time.".to_string(), vec![4])]);
+    }
+
+    #[test]
+    fn assign_while_borrowed_in_a_loop() {
+        let errors = check("
+            let mut x: int32 = 0;
+            let zero: int32 = 0;
+            let mut r = &zero;
+            let mut i: int32 = 0;
+            while i < 2 {
+                x = i;
+                let y = *r;
+                r = &x;
+                i += 1;
+            }
+        ");
+        assert_eq!(errors, [(7, "Cannot assign to 'x' because it is borrowed.".to_string(), vec![9])]);
+    }
+
+    #[test]
+    fn borrows_end_at_their_last_use() {
+        let errors = check("
+            let mut x: int32 = 1;
+            let r = &mut x;
+            *r = 2;
+            let y = x;
+            let mut w: int32 = 0;
+            let mut held = &mut w;
+            loop {
+                let t = &mut w;
+                held = t;
+                if *held > 3 { break; }
+            }
+        ");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn reference_outlives_its_local() {
+        let errors = check("
+            let zero: int32 = 0;
+            let mut p = &zero;
+            {
+                let z: int32 = 3;
+                p = &z;
+            }
+            let q = *p;
+        ");
+        assert_eq!(errors, [(6, "'z' does not live long enough.".to_string(), vec![7])]);
+    }
+
+    #[test]
+    fn dangling_reference_never_used() {
+        let errors = check("
+            let s = {
+                let inner: int32 = 5;
+                &inner
+            };
+        ");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn return_reference_to_local() {
+        let errors = check("
+            fn f() -> &int32 {
+                let z: int32 = 3;
+                &z
+            }
+        ");
+        assert_eq!(errors, [(4, "Cannot return a reference to local variable 'z'.".to_string(), vec![5])]);
+    }
+}
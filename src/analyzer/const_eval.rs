@@ -0,0 +1,543 @@
+//! The module of `ConstEvaluator`, which folds constant operations and reports those that overflow or divide by zero
+
+use crate::analyzer::error::CompileError;
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::expr::{Expr, ExprLiteral, ExprType};
+use crate::ast::program::Program;
+use crate::ast::stmt::{Stmt, StmtType};
+use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+pub struct ConstEvaluator<'a> {
+    type_interner: &'a TypeInterner,
+    errors: Vec<CompileError>,
+}
+
+/// An integer constant widened to 128 bits, keeping the signedness of its type.
+#[derive(Clone, Copy)]
+enum Int {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+/// Why an operation on constants gives no constant.
+enum ConstError {
+    /// The result does not fit in the type of the operation.
+    Overflow,
+    DivideByZero,
+    /// The shift amount is negative or not smaller than the bit width.
+    ShiftOverflow,
+    /// The operation is left to run with the program.
+    NotConstant,
+}
+
+/// Apply an arithmetic operator to two 128-bit integers of the same signedness, `None` if the result overflows.
+macro_rules! checked_arith {
+    ($ope:expr, $lhs:expr, $rhs:expr) => {
+        match $ope {
+            BinaryOperator::Plus => $lhs.checked_add($rhs),
+            BinaryOperator::Minus => $lhs.checked_sub($rhs),
+            BinaryOperator::Multiply => $lhs.checked_mul($rhs),
+            BinaryOperator::Divide => $lhs.checked_div($rhs),
+            BinaryOperator::Remainder => $lhs.checked_rem($rhs),
+            BinaryOperator::BitAnd => Some($lhs & $rhs),
+            BinaryOperator::BitOr => Some($lhs | $rhs),
+            BinaryOperator::BitXor => Some($lhs ^ $rhs),
+            _ => unreachable!(),
+        }
+    };
+}
+
+impl<'a> ConstEvaluator<'a> {
+    #[must_use]
+    pub fn new(type_interner: &'a TypeInterner) -> Self {
+        Self { type_interner, errors: vec![] }
+    }
+
+    /// Fold the constant operations of the whole program in place, and report those that cannot be evaluated.
+    pub fn fold(mut self, program: &mut Program) -> Vec<CompileError> {
+        for stmt in &mut program.stmts {
+            self.fold_statement(stmt);
+        }
+        if let Some(tail) = &mut program.tail {
+            self.fold_expression(tail);
+        }
+        self.errors
+    }
+
+    fn fold_statement(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.stmt_type {
+            StmtType::Let(stmt_let) => {
+                self.fold_expression(&mut stmt_let.init);
+            }
+            StmtType::Assign(assign) => {
+                self.fold_expression(&mut assign.target);
+                let value = self.fold_expression(&mut assign.value);
+                if let Some(ope) = assign.ope {
+                    self.check_shift_amount(&stmt.pos, ope, assign.target.type_id, value.as_ref());
+                }
+            }
+            StmtType::Expr(stmt_expr) => {
+                self.fold_expression(&mut stmt_expr.expr);
+            }
+            StmtType::Fn(stmt_fn) => {
+                self.fold_expression(&mut stmt_fn.body);
+            }
+            StmtType::Return(stmt_return) => {
+                if let Some(value) = &mut stmt_return.value {
+                    self.fold_expression(value);
+                }
+            }
+            StmtType::Break(stmt_break) => {
+                if let Some(value) = &mut stmt_break.value {
+                    self.fold_expression(value);
+                }
+            }
+            StmtType::Continue(_) => {}
+        }
+    }
+
+    /// Fold the constant operations in `expr`, and give its value if the whole expression is a constant.
+    fn fold_expression(&mut self, expr: &mut Expr) -> Option<Value> {
+        let type_id = expr.type_id;
+        let folded = match &mut expr.expr_type {
+            ExprType::Literal(literal) => return Some(Self::typed_value(&literal.value, type_id)),
+            ExprType::Unary(unary) => {
+                // A negative untyped literal was checked as a whole, and its magnitude alone may not fit, as in
+                // `let x: int8 = -128`.
+                if let (UnaryOperator::Minus, ExprType::Literal(ExprLiteral { value: Value::Integer(ValueInteger::Untyped(int)) })) = (unary.ope, &unary.rhs.expr_type) && Self::is_signed(type_id) {
+                    Self::int_value(Int::Signed((*int as i128).wrapping_neg()), type_id).ok()
+                } else {
+                    let rhs = self.fold_expression(&mut unary.rhs)?;
+                    let result = Self::eval_unary(unary.ope, type_id, &rhs);
+                    let operation = || if Self::is_negative(&rhs) {
+                        format!("{}({})", unary.ope, Self::show(&rhs))
+                    } else {
+                        format!("{}{}", unary.ope, Self::show(&rhs))
+                    };
+                    self.check(&expr.pos, result, operation, type_id)
+                }
+            }
+            ExprType::Binary(binary) => {
+                let lhs = self.fold_expression(&mut binary.lhs);
+                let rhs = self.fold_expression(&mut binary.rhs);
+                let operand_ty = binary.lhs.type_id;
+                let (Some(lhs), Some(rhs)) = (&lhs, &rhs) else {
+                    self.check_shift_amount(&expr.pos, binary.ope, operand_ty, rhs.as_ref());
+                    return None;
+                };
+                let result = Self::eval_binary(binary.ope, operand_ty, lhs, rhs);
+                self.check(&expr.pos, result, || format!("{} {} {}", Self::show(lhs), binary.ope, Self::show(rhs)), operand_ty)
+            }
+            ExprType::Cast(cast) => {
+                let value = self.fold_expression(&mut cast.expr)?;
+                Self::eval_cast(&value, type_id)
+            }
+            ExprType::Block(block) => {
+                for stmt in &mut block.stmts {
+                    self.fold_statement(stmt);
+                }
+                if let Some(tail) = &mut block.tail {
+                    self.fold_expression(tail);
+                }
+                None
+            }
+            ExprType::Call(call) => {
+                for arg in &mut call.args {
+                    self.fold_expression(arg);
+                }
+                None
+            }
+            ExprType::If(expr_if) => {
+                self.fold_expression(&mut expr_if.cond);
+                self.fold_expression(&mut expr_if.then_branch);
+                if let Some(else_branch) = &mut expr_if.else_branch {
+                    self.fold_expression(else_branch);
+                }
+                None
+            }
+            ExprType::While(expr_while) => {
+                self.fold_expression(&mut expr_while.cond);
+                self.fold_expression(&mut expr_while.body);
+                None
+            }
+            ExprType::Loop(expr_loop) => {
+                self.fold_expression(&mut expr_loop.body);
+                None
+            }
+            ExprType::Deref(deref) => {
+                self.fold_expression(&mut deref.expr);
+                None
+            }
+            ExprType::Variable(_) | ExprType::Borrow(_) | ExprType::Error => None,
+        };
+
+        if let Some(value) = &folded {
+            expr.expr_type = ExprType::Literal(ExprLiteral { value: value.clone() });
+        }
+        folded
+    }
+
+    /// Report an operation that cannot be evaluated, where `operation` describes it with the values of its operands.
+    fn check(&mut self, pos: &Position, result: Result<Value, ConstError>, operation: impl FnOnce() -> String, type_id: TypeId) -> Option<Value> {
+        let msg = match result {
+            Ok(value) => return Some(value),
+            Err(ConstError::NotConstant) => return None,
+            Err(ConstError::Overflow) => format!("The result of '{}' is out of range for type '{}'.", operation(), self.type_interner.to_data_type[&type_id]),
+            Err(ConstError::DivideByZero) => format!("Cannot evaluate '{}' because it divides by zero.", operation()),
+            Err(ConstError::ShiftOverflow) => {
+                format!("The shift amount must be smaller than the bit width of type '{}' ({} bits).", self.type_interner.to_data_type[&type_id], Self::int_bit_width(type_id))
+            }
+        };
+        self.errors.push(CompileError::new(pos.clone(), msg));
+        None
+    }
+
+    /// Report a shift by a constant amount that is not smaller than the bit width, whatever the shifted value is.
+    fn check_shift_amount(&mut self, pos: &Position, ope: BinaryOperator, type_id: TypeId, amount: Option<&Value>) {
+        if let (BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight, Some(Value::Integer(amount))) = (ope, amount) && Self::shift_amount(Self::int_of(amount, type_id), type_id).is_err() {
+            self.check(pos, Err(ConstError::ShiftOverflow), String::new, type_id);
+        }
+    }
+
+    fn eval_unary(ope: UnaryOperator, type_id: TypeId, rhs: &Value) -> Result<Value, ConstError> {
+        match (ope, rhs) {
+            (UnaryOperator::Plus, _) => Ok(rhs.clone()),
+            (UnaryOperator::Minus, Value::Integer(int)) => match Self::int_of(int, type_id) {
+                Int::Signed(int) => Self::int_value(Int::Signed(int.checked_neg().ok_or(ConstError::Overflow)?), type_id),
+                Int::Unsigned(_) => unreachable!("Unsigned integers have no negative sign."),
+            },
+            (UnaryOperator::Minus, Value::Float(float)) => Ok(Self::float_value(-Self::float_of(float), type_id)),
+            (UnaryOperator::Not, Value::Bool(boolean)) => Ok(Value::Bool(!boolean)),
+            (UnaryOperator::BitNot, Value::Integer(int)) => Ok(Self::wrap_int(!Self::int_bits(Self::int_of(int, type_id)), type_id)),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+
+    /// Evaluate a binary operation whose operands have type `type_id`.
+    fn eval_binary(ope: BinaryOperator, type_id: TypeId, lhs: &Value, rhs: &Value) -> Result<Value, ConstError> {
+        match (lhs, rhs) {
+            (Value::Integer(lhs), Value::Integer(rhs)) => Self::eval_int_binary(ope, type_id, Self::int_of(lhs, type_id), Self::int_of(rhs, type_id)),
+            (Value::Float(lhs), Value::Float(rhs)) => Ok(Self::eval_float_binary(ope, type_id, Self::float_of(lhs), Self::float_of(rhs))),
+            (Value::Bool(lhs), Value::Bool(rhs)) => match ope {
+                BinaryOperator::And | BinaryOperator::BitAnd => Ok(Value::Bool(*lhs && *rhs)),
+                BinaryOperator::Or | BinaryOperator::BitOr => Ok(Value::Bool(*lhs || *rhs)),
+                BinaryOperator::BitXor => Ok(Value::Bool(lhs != rhs)),
+                _ => Ok(Value::Bool(Self::compare(ope, lhs.partial_cmp(rhs)))),
+            },
+            (Value::Char(lhs), Value::Char(rhs)) => Ok(Value::Bool(Self::compare(ope, lhs.partial_cmp(rhs)))),
+            _ => Err(ConstError::NotConstant),
+        }
+    }
+
+    fn eval_int_binary(ope: BinaryOperator, type_id: TypeId, lhs: Int, rhs: Int) -> Result<Value, ConstError> {
+        let ordering = match (lhs, rhs) {
+            (Int::Signed(lhs), Int::Signed(rhs)) => lhs.cmp(&rhs),
+            (Int::Unsigned(lhs), Int::Unsigned(rhs)) => lhs.cmp(&rhs),
+            _ => unreachable!(),
+        };
+        let result = match ope {
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::Less |
+            BinaryOperator::LessEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual => {
+                return Ok(Value::Bool(Self::compare(ope, Some(ordering))));
+            }
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                let amount = Self::shift_amount(rhs, type_id)?;
+                return Ok(match (ope, lhs) {
+                    (BinaryOperator::ShiftLeft, _) => Self::wrap_int(Self::int_bits(lhs) << amount, type_id),
+                    (_, Int::Signed(lhs)) => Self::wrap_int((lhs >> amount) as u128, type_id),
+                    (_, Int::Unsigned(lhs)) => Self::wrap_int(lhs >> amount, type_id),
+                });
+            }
+            BinaryOperator::Divide | BinaryOperator::Remainder if Self::int_bits(rhs) == 0 => return Err(ConstError::DivideByZero),
+            // `MIN % -1` is 0, but the division it comes from overflows as `MIN / -1` does.
+            BinaryOperator::Remainder if matches!(rhs, Int::Signed(-1)) && Self::eval_int_binary(BinaryOperator::Divide, type_id, lhs, rhs).is_err() => {
+                return Err(ConstError::Overflow);
+            }
+            BinaryOperator::Power => match (lhs, rhs) {
+                (Int::Signed(base), Int::Signed(exp)) if exp < 0 => Some(Int::Signed(match base {
+                    1 => 1,
+                    -1 if exp % 2 == 0 => 1,
+                    -1 => -1,
+                    _ => 0,
+                })),
+                (Int::Signed(base), Int::Signed(exp)) => match u32::try_from(exp) {
+                    Ok(exp) => base.checked_pow(exp),
+                    Err(_) if matches!(base, 0 | 1) => Some(base),
+                    Err(_) if base == -1 => Some(if exp % 2 == 0 { 1 } else { -1 }),
+                    Err(_) => None,
+                }.map(Int::Signed),
+                (Int::Unsigned(base), Int::Unsigned(exp)) => match u32::try_from(exp) {
+                    Ok(exp) => base.checked_pow(exp),
+                    Err(_) if matches!(base, 0 | 1) => Some(base),
+                    Err(_) => None,
+                }.map(Int::Unsigned),
+                _ => unreachable!(),
+            },
+            _ => match (lhs, rhs) {
+                (Int::Signed(lhs), Int::Signed(rhs)) => checked_arith!(ope, lhs, rhs).map(Int::Signed),
+                (Int::Unsigned(lhs), Int::Unsigned(rhs)) => checked_arith!(ope, lhs, rhs).map(Int::Unsigned),
+                _ => unreachable!(),
+            },
+        };
+        result.map_or(Err(ConstError::Overflow), |result| Self::int_value(result, type_id))
+    }
+
+    /// Evaluate a float operation, in single precision for `float32` as the compiled program does.
+    fn eval_float_binary(ope: BinaryOperator, type_id: TypeId, lhs: f64, rhs: f64) -> Value {
+        if let BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::Less |
+               BinaryOperator::LessEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual = ope {
+            return Value::Bool(Self::compare(ope, lhs.partial_cmp(&rhs)));
+        }
+        if type_id == TypeId::FLOAT32 {
+            let (lhs, rhs) = (lhs as f32, rhs as f32);
+            let result = match ope {
+                BinaryOperator::Plus => lhs + rhs,
+                BinaryOperator::Minus => lhs - rhs,
+                BinaryOperator::Multiply => lhs * rhs,
+                BinaryOperator::Divide => lhs / rhs,
+                BinaryOperator::Remainder => lhs % rhs,
+                BinaryOperator::Power => lhs.powf(rhs),
+                _ => unreachable!(),
+            };
+            Value::Float(ValueFloat::Float32(result))
+        } else {
+            let result = match ope {
+                BinaryOperator::Plus => lhs + rhs,
+                BinaryOperator::Minus => lhs - rhs,
+                BinaryOperator::Multiply => lhs * rhs,
+                BinaryOperator::Divide => lhs / rhs,
+                BinaryOperator::Remainder => lhs % rhs,
+                BinaryOperator::Power => lhs.powf(rhs),
+                _ => unreachable!(),
+            };
+            Value::Float(ValueFloat::Float64(result))
+        }
+    }
+
+    /// Convert a constant to `dst_ty` as a cast does: integers wrap, and floats saturate when converted to integers.
+    fn eval_cast(value: &Value, dst_ty: TypeId) -> Option<Value> {
+        let is_int = !matches!(dst_ty, TypeId::FLOAT32 | TypeId::FLOAT64 | TypeId::CHAR);
+        let int = match value {
+            Value::Integer(int) => Self::int_of(int, if Self::is_signed(dst_ty) { TypeId::INT128 } else { TypeId::UINT128 }),
+            Value::Bool(boolean) => Int::Unsigned(u128::from(*boolean)),
+            Value::Char(ch) => Int::Unsigned(u128::from(*ch)),
+            Value::Float(float) if is_int => return Some(Self::saturate_float(Self::float_of(float), dst_ty)),
+            Value::Float(float) => return Some(Self::float_value(Self::float_of(float), dst_ty)),
+            Value::Str(_) => return None,
+        };
+        match (int, dst_ty) {
+            (Int::Unsigned(int), TypeId::CHAR) => u8::try_from(int).ok().map(|int| Value::Char(char::from(int))),
+            (Int::Signed(int), TypeId::FLOAT32) => Some(Value::Float(ValueFloat::Float32(int as f32))),
+            (Int::Unsigned(int), TypeId::FLOAT32) => Some(Value::Float(ValueFloat::Float32(int as f32))),
+            (Int::Signed(int), TypeId::FLOAT64) => Some(Value::Float(ValueFloat::Float64(int as f64))),
+            (Int::Unsigned(int), TypeId::FLOAT64) => Some(Value::Float(ValueFloat::Float64(int as f64))),
+            _ => Some(Self::wrap_int(Self::int_bits(int), dst_ty)),
+        }
+    }
+
+    /// Check that a shift amount is smaller than the bit width of `type_id`.
+    fn shift_amount(amount: Int, type_id: TypeId) -> Result<u32, ConstError> {
+        let amount = match amount {
+            Int::Signed(amount) => u32::try_from(amount).ok(),
+            Int::Unsigned(amount) => u32::try_from(amount).ok(),
+        };
+        amount.filter(|amount| *amount < Self::int_bit_width(type_id)).ok_or(ConstError::ShiftOverflow)
+    }
+
+    fn compare(ope: BinaryOperator, ordering: Option<Ordering>) -> bool {
+        match ope {
+            BinaryOperator::Equal => ordering == Some(Ordering::Equal),
+            BinaryOperator::NotEqual => ordering != Some(Ordering::Equal),
+            BinaryOperator::Less => ordering == Some(Ordering::Less),
+            BinaryOperator::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            BinaryOperator::Greater => ordering == Some(Ordering::Greater),
+            BinaryOperator::GreaterEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Give a literal the type it was checked to have.
+    fn typed_value(value: &Value, type_id: TypeId) -> Value {
+        match value {
+            Value::Integer(int @ ValueInteger::Untyped(_)) => Self::wrap_int(Self::int_bits(Self::int_of(int, type_id)), type_id),
+            Value::Float(float @ ValueFloat::Untyped(_)) => Self::float_value(Self::float_of(float), type_id),
+            _ => value.clone(),
+        }
+    }
+
+    fn is_signed(type_id: TypeId) -> bool {
+        matches!(type_id, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128)
+    }
+
+    fn int_bit_width(type_id: TypeId) -> u32 {
+        match type_id {
+            TypeId::INT8 | TypeId::UINT8 => 8,
+            TypeId::INT16 | TypeId::UINT16 => 16,
+            TypeId::INT32 | TypeId::UINT32 => 32,
+            TypeId::INT64 | TypeId::UINT64 => 64,
+            TypeId::INT128 | TypeId::UINT128 => 128,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Widen an integer, reading an untyped one with the signedness of `type_id`.
+    fn int_of(int: &ValueInteger, type_id: TypeId) -> Int {
+        match int {
+            ValueInteger::Int8(int) => Int::Signed(i128::from(*int)),
+            ValueInteger::Int16(int) => Int::Signed(i128::from(*int)),
+            ValueInteger::Int32(int) => Int::Signed(i128::from(*int)),
+            ValueInteger::Int64(int) => Int::Signed(i128::from(*int)),
+            ValueInteger::Int128(int) => Int::Signed(*int),
+            ValueInteger::UInt8(int) => Int::Unsigned(u128::from(*int)),
+            ValueInteger::UInt16(int) => Int::Unsigned(u128::from(*int)),
+            ValueInteger::UInt32(int) => Int::Unsigned(u128::from(*int)),
+            ValueInteger::UInt64(int) => Int::Unsigned(u128::from(*int)),
+            ValueInteger::UInt128(int) => Int::Unsigned(*int),
+            ValueInteger::Untyped(int) if Self::is_signed(type_id) => Int::Signed(*int as i128),
+            ValueInteger::Untyped(int) => Int::Unsigned(*int),
+        }
+    }
+
+    /// The bits of a widened integer, sign-extended for a signed one.
+    fn int_bits(int: Int) -> u128 {
+        match int {
+            Int::Signed(int) => int as u128,
+            Int::Unsigned(int) => int,
+        }
+    }
+
+    /// Narrow an integer to `type_id`, failing if it is out of range.
+    fn int_value(int: Int, type_id: TypeId) -> Result<Value, ConstError> {
+        let int = match (int, type_id) {
+            (Int::Signed(int), TypeId::INT8) => i8::try_from(int).ok().map(ValueInteger::Int8),
+            (Int::Signed(int), TypeId::INT16) => i16::try_from(int).ok().map(ValueInteger::Int16),
+            (Int::Signed(int), TypeId::INT32) => i32::try_from(int).ok().map(ValueInteger::Int32),
+            (Int::Signed(int), TypeId::INT64) => i64::try_from(int).ok().map(ValueInteger::Int64),
+            (Int::Signed(int), TypeId::INT128) => Some(ValueInteger::Int128(int)),
+            (Int::Unsigned(int), TypeId::UINT8) => u8::try_from(int).ok().map(ValueInteger::UInt8),
+            (Int::Unsigned(int), TypeId::UINT16) => u16::try_from(int).ok().map(ValueInteger::UInt16),
+            (Int::Unsigned(int), TypeId::UINT32) => u32::try_from(int).ok().map(ValueInteger::UInt32),
+            (Int::Unsigned(int), TypeId::UINT64) => u64::try_from(int).ok().map(ValueInteger::UInt64),
+            (Int::Unsigned(int), TypeId::UINT128) => Some(ValueInteger::UInt128(int)),
+            _ => unreachable!(),
+        };
+        int.map(Value::Integer).ok_or(ConstError::Overflow)
+    }
+
+    /// Truncate the bits of an integer to `type_id`.
+    fn wrap_int(bits: u128, type_id: TypeId) -> Value {
+        Value::Integer(match type_id {
+            TypeId::INT8 => ValueInteger::Int8(bits as i8),
+            TypeId::UINT8 => ValueInteger::UInt8(bits as u8),
+            TypeId::INT16 => ValueInteger::Int16(bits as i16),
+            TypeId::UINT16 => ValueInteger::UInt16(bits as u16),
+            TypeId::INT32 => ValueInteger::Int32(bits as i32),
+            TypeId::UINT32 => ValueInteger::UInt32(bits as u32),
+            TypeId::INT64 => ValueInteger::Int64(bits as i64),
+            TypeId::UINT64 => ValueInteger::UInt64(bits as u64),
+            TypeId::INT128 => ValueInteger::Int128(bits as i128),
+            TypeId::UINT128 => ValueInteger::UInt128(bits),
+            _ => unreachable!(),
+        })
+    }
+
+    fn saturate_float(float: f64, type_id: TypeId) -> Value {
+        Value::Integer(match type_id {
+            TypeId::INT8 => ValueInteger::Int8(float as i8),
+            TypeId::UINT8 => ValueInteger::UInt8(float as u8),
+            TypeId::INT16 => ValueInteger::Int16(float as i16),
+            TypeId::UINT16 => ValueInteger::UInt16(float as u16),
+            TypeId::INT32 => ValueInteger::Int32(float as i32),
+            TypeId::UINT32 => ValueInteger::UInt32(float as u32),
+            TypeId::INT64 => ValueInteger::Int64(float as i64),
+            TypeId::UINT64 => ValueInteger::UInt64(float as u64),
+            TypeId::INT128 => ValueInteger::Int128(float as i128),
+            TypeId::UINT128 => ValueInteger::UInt128(float as u128),
+            _ => unreachable!(),
+        })
+    }
+
+    fn float_of(float: &ValueFloat) -> f64 {
+        match float {
+            ValueFloat::Float32(float) => f64::from(*float),
+            ValueFloat::Float64(float) | ValueFloat::Untyped(float) => *float,
+        }
+    }
+
+    fn float_value(float: f64, type_id: TypeId) -> Value {
+        Value::Float(match type_id {
+            TypeId::FLOAT32 => ValueFloat::Float32(float as f32),
+            _ => ValueFloat::Float64(float),
+        })
+    }
+
+    fn is_negative(value: &Value) -> bool {
+        match value {
+            Value::Integer(int) => matches!(Self::int_of(int, TypeId::UINT128), Int::Signed(int) if int < 0),
+            Value::Float(float) => Self::float_of(float).is_sign_negative(),
+            _ => false,
+        }
+    }
+
+    /// Write a constant as it would appear in the source, without its type.
+    fn show(value: &Value) -> String {
+        match value {
+            Value::Integer(int) => Self::int_of(int, TypeId::UINT128).to_string(),
+            Value::Float(float) => Self::float_of(float).to_string(),
+            Value::Bool(boolean) => boolean.to_string(),
+            Value::Char(ch) => format!("{:?}", ch),
+            Value::Str(string) => format!("{:?}", string),
+        }
+    }
+}
+
+impl Display for Int {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Signed(int) => write!(f, "{}", int),
+            Self::Unsigned(int) => write!(f, "{}", int),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::tests::check;
+
+    #[test]
+    fn overflow() {
+        assert_eq!(check("let a: int8 = 100i8 + 100i8;"), ["The result of '100 + 100' is out of range for type 'int8'."]);
+        assert_eq!(check("let a: uint8 = 0u8 - 1u8;"), ["The result of '0 - 1' is out of range for type 'uint8'."]);
+        assert_eq!(check("let a = 2i32 ** 31;"), ["The result of '2 ** 31' is out of range for type 'int32'."]);
+        assert_eq!(check("let a: int8 = -(-127i8 - 1i8);"), ["The result of '-(-128)' is out of range for type 'int8'."]);
+    }
+
+    #[test]
+    fn values_at_the_bounds() {
+        assert!(check("let a: int8 = -128; let b: int8 = -127i8 - 1i8; let c: uint8 = 255u8; let d = (-2i32) ** 31;").is_empty());
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(check("let a = 1 / 0;"), ["Cannot evaluate '1 / 0' because it divides by zero."]);
+        assert_eq!(check("let a = 7 % 0;"), ["Cannot evaluate '7 % 0' because it divides by zero."]);
+    }
+
+    #[test]
+    fn min_divided_by_minus_one() {
+        assert_eq!(check("let a: int32 = -2147483648 % -1;"), ["The result of '-2147483648 % -1' is out of range for type 'int32'."]);
+        assert_eq!(check("let a: int32 = -2147483648 / -1;"), ["The result of '-2147483648 / -1' is out of range for type 'int32'."]);
+        assert!(check("let a: int32 = 2147483647 % -1;").is_empty());
+    }
+
+    #[test]
+    fn shift_amount_reported_once() {
+        assert_eq!(check("let x = 1; let y = x << 32;"), ["The shift amount must be smaller than the bit width of type 'int32' (32 bits)."]);
+        assert_eq!(check("let mut x = 1uint8; x >>= 8;"), ["The shift amount must be smaller than the bit width of type 'uint8' (8 bits)."]);
+    }
+}
@@ -6,6 +6,7 @@ use crate::main_error::IrantaCompilerError;
 pub struct CompileError {
     pub pos: Position,
     pub msg: String,
+    pub notes: Vec<(Position, String)>,
 }
 
 pub struct CompileErrorList {
@@ -20,18 +21,29 @@ impl IrantaCompilerError for CompileError {
     fn get_msg(&self) -> &str {
         &self.msg
     }
+
+    fn get_notes(&self) -> Vec<(&Position, &str)> {
+        self.notes.iter().map(|(pos, msg)| (pos, msg.as_str())).collect()
+    }
 }
 
 impl CompileError {
     #[must_use]
     pub fn new(pos: Position, msg: String) -> Self {
-        Self { pos, msg }
+        Self { pos, msg, notes: vec![] }
+    }
+
+    /// Point out another place in the source related to the error.
+    #[must_use]
+    pub fn with_note(mut self, pos: Position, msg: String) -> Self {
+        self.notes.push((pos, msg));
+        self
     }
 }
 
-impl Into<CompileErrorList> for CompileError {
-    fn into(self) -> CompileErrorList {
-        CompileErrorList::new(vec![self])
+impl From<CompileError> for CompileErrorList {
+    fn from(value: CompileError) -> Self {
+        Self::new(vec![value])
     }
 }
 
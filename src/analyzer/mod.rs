@@ -0,0 +1,1017 @@
+//! The module of `Analyzer`, which checks the types of a program and annotates its nodes before code generation
+
+pub mod borrowck;
+pub mod const_eval;
+pub mod error;
+pub mod ownership;
+
+use crate::analyzer::const_eval::ConstEvaluator;
+use crate::analyzer::error::{CompileError, CompileErrorList, CompileResultList};
+use crate::analyzer::ownership::MoveChecker;
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::expr::{Expr, ExprBinary, ExprBlock, ExprBorrow, ExprCall, ExprCast, ExprDeref, ExprIf, ExprLiteral, ExprLoop, ExprType, ExprUnary, ExprVariable, ExprWhile};
+use crate::ast::program::Program;
+use crate::ast::stmt::{Stmt, StmtAssign, StmtBreak, StmtContinue, StmtFn, StmtLet, StmtReturn, StmtType};
+use crate::ast::type_expr::{TypeExpr, TypeExprType};
+use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
+use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+pub struct Analyzer {
+    symbols: SymbolInterner,
+    type_interner: TypeInterner,
+    /// The variables of earlier REPL inputs, seen at the top level of the program.
+    globals: HashMap<Symbol, Variable>,
+    /// Where each top-level variable was moved, if it was, from earlier REPL inputs and then to the end of the program.
+    global_moves: HashMap<Symbol, Option<Position>>,
+    scopes: Vec<HashMap<Symbol, Variable>>,
+    functions: HashMap<Symbol, Function>,
+    current_return_type: Option<TypeId>,
+    loops: Vec<LoopContext>,
+    errors: Vec<CompileError>,
+    /// Whether some statements failed to parse and were left out of the program, which may have declared names.
+    has_syntax_errors: bool,
+}
+
+/// A variable in scope with its type.
+struct Variable {
+    type_id: TypeId,
+    mutable: bool,
+}
+
+/// The signature of a user-defined function.
+struct Function {
+    param_types: Vec<TypeId>,
+    return_type: TypeId,
+}
+
+/// A loop being analyzed, and the type of the values its `break` statements carry so far.
+struct LoopContext {
+    label: Option<Symbol>,
+    allow_break_value: bool,
+    break_type: Option<TypeId>,
+}
+
+impl Analyzer {
+    #[must_use]
+    pub fn new(symbols: SymbolInterner, type_interner: TypeInterner) -> Self {
+        Self {
+            symbols,
+            type_interner,
+            globals: HashMap::new(),
+            global_moves: HashMap::new(),
+            scopes: vec![],
+            functions: HashMap::new(),
+            current_return_type: None,
+            loops: vec![],
+            errors: vec![],
+            has_syntax_errors: false,
+        }
+    }
+
+    #[must_use]
+    pub fn into_parts(self) -> (SymbolInterner, TypeInterner) {
+        (self.symbols, self.type_interner)
+    }
+
+    /// Declare a top-level variable of an earlier REPL input, with where its value was moved if it was.
+    pub fn declare_global(&mut self, name: Symbol, type_id: TypeId, mutable: bool, moved: Option<Position>) {
+        self.globals.insert(name, Variable {
+            type_id,
+            mutable,
+        });
+        self.global_moves.insert(name, moved);
+    }
+
+    /// Give back where each top-level variable was moved at the end of the program, for the next REPL input.
+    pub fn take_global_moves(&mut self) -> HashMap<Symbol, Option<Position>> {
+        std::mem::take(&mut self.global_moves)
+    }
+
+    /// Declare a function of an earlier REPL input.
+    pub fn declare_extern_function(&mut self, name: Symbol, param_types: Vec<TypeId>, return_type: TypeId) {
+        self.functions.insert(name, Function {
+            param_types,
+            return_type,
+        });
+    }
+
+    /// Check the whole program, filling the `type_id` of every expression and type, and report all errors found.
+    pub fn analyze(&mut self, program: &mut Program, has_syntax_errors: bool) -> CompileResultList<()> {
+        self.has_syntax_errors = has_syntax_errors;
+        for stmt in &mut program.stmts {
+            if let StmtType::Fn(stmt_fn) = &mut stmt.stmt_type {
+                self.declare_function(stmt_fn);
+            }
+        }
+
+        self.scopes.push(std::mem::take(&mut self.globals));
+        for stmt in &mut program.stmts {
+            if !matches!(stmt.stmt_type, StmtType::Fn(_)) {
+                self.analyze_statement(stmt);
+            }
+        }
+        if let Some(tail) = &mut program.tail {
+            self.analyze_expression(tail, None);
+        }
+        self.scopes.pop();
+
+        for stmt in &mut program.stmts {
+            if let StmtType::Fn(stmt_fn) = &mut stmt.stmt_type {
+                self.analyze_function(stmt_fn);
+            }
+        }
+
+        // Moves are only tracked and constants only folded in a well-formed and well-typed program, as both depend on the
+        // types of the expressions. Borrows are checked later, on the MIR lowered from the program.
+        if self.errors.is_empty() && !has_syntax_errors {
+            self.errors = MoveChecker::new(&self.symbols, &self.type_interner).check(program, &mut self.global_moves);
+            self.errors.extend(ConstEvaluator::new(&self.type_interner).fold(program));
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CompileErrorList::new(std::mem::take(&mut self.errors)))
+        }
+    }
+
+    fn error(&mut self, pos: &Position, msg: String) {
+        self.errors.push(CompileError::new(pos.clone(), msg));
+    }
+
+    /// Report a use of a name that is not declared, unless a statement failed to parse, as it may have declared it.
+    fn undefined(&mut self, pos: &Position, msg: String) {
+        if !self.has_syntax_errors {
+            self.error(pos, msg);
+        }
+    }
+
+    fn unsupported_operator(&mut self, pos: &Position, ope: impl std::fmt::Display, type_id: TypeId) {
+        let msg = format!("Cannot use operator '{}' on type '{}'.", ope, self.type_interner.to_data_type[&type_id]);
+        self.error(pos, msg);
+    }
+
+    /// Report that `found` is not `expected`, unless one of them already comes from an error.
+    fn type_mismatch(&mut self, pos: &Position, expected: TypeId, found: TypeId) {
+        if expected != TypeId::ERROR && found != TypeId::ERROR {
+            let msg = format!("Expected type '{}', but found '{}'.", self.type_interner.to_data_type[&expected], self.type_interner.to_data_type[&found]);
+            self.error(pos, msg);
+        }
+    }
+
+    fn resolve_type(&mut self, type_expr: &mut TypeExpr) -> TypeId {
+        let type_id = match &mut type_expr.type_expr_type {
+            TypeExprType::Named(name) => {
+                let name = self.symbols.get_name(*name);
+                match self.type_interner.find_type(name) {
+                    Some(TypeId::NEVER) => {
+                        self.error(&type_expr.pos, "Cannot use type 'never' here.".to_string());
+                        TypeId::ERROR
+                    }
+                    Some(type_id) => type_id,
+                    None => {
+                        let msg = format!("Unknown type '{}'.", name);
+                        self.error(&type_expr.pos, msg);
+                        TypeId::ERROR
+                    }
+                }
+            }
+            TypeExprType::Ref { mutable, target } => {
+                let mutable = *mutable;
+                match self.resolve_type(target) {
+                    TypeId::ERROR => TypeId::ERROR,
+                    TypeId::VOID => {
+                        self.error(&target.pos, "Cannot use type 'void' here.".to_string());
+                        TypeId::ERROR
+                    }
+                    target_ty => self.type_interner.ref_type(target_ty, mutable),
+                }
+            }
+        };
+        type_expr.type_id = type_id;
+        type_id
+    }
+
+    fn find_variable(&self, name: Symbol) -> Option<&Variable> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&name))
+    }
+
+    fn declare_function(&mut self, stmt_fn: &mut StmtFn) {
+        let mut param_types = vec![];
+        for param in &mut stmt_fn.params {
+            let param_type = self.resolve_type(&mut param.param_type);
+            if param_type == TypeId::VOID {
+                self.error(&param.param_type.pos, "Cannot use type 'void' here.".to_string());
+            }
+            param_types.push(param_type);
+        }
+        let return_type = match &mut stmt_fn.return_type {
+            Some(return_type) => self.resolve_type(return_type),
+            None => TypeId::VOID,
+        };
+
+        let name = self.symbols.get_name(stmt_fn.name);
+        if name == "main" {
+            self.error(&stmt_fn.name_pos, "The function name 'main' is reserved.".to_string());
+        } else if let Entry::Vacant(entry) = self.functions.entry(stmt_fn.name) {
+            entry.insert(Function {
+                param_types,
+                return_type,
+            });
+        } else {
+            let msg = format!("Function '{}' is already defined.", name);
+            self.error(&stmt_fn.name_pos, msg);
+        }
+    }
+
+    fn analyze_function(&mut self, stmt_fn: &mut StmtFn) {
+        let mut params = HashMap::new();
+        for param in &stmt_fn.params {
+            params.insert(param.name, Variable {
+                type_id: param.param_type.type_id,
+                mutable: param.mutable,
+            });
+        }
+        let return_type = stmt_fn.return_type.as_ref().map_or(TypeId::VOID, |return_type| return_type.type_id);
+
+        self.scopes = vec![params];
+        self.loops.clear();
+        self.current_return_type = Some(return_type);
+        let body_ty = self.analyze_expression(&mut stmt_fn.body, Some(return_type));
+        self.current_return_type = None;
+        self.scopes.clear();
+
+        if body_ty != TypeId::NEVER && body_ty != return_type {
+            let ExprType::Block(body) = &stmt_fn.body.expr_type else { unreachable!() };
+            let pos = body.tail.as_ref().map_or(&stmt_fn.body.pos, |tail| &tail.pos).clone();
+            self.type_mismatch(&pos, return_type, body_ty);
+        }
+    }
+
+    /// Analyze a statement, returning `TypeId::NEVER` if the control flow never goes past it and `TypeId::VOID` otherwise.
+    fn analyze_statement(&mut self, stmt: &mut Stmt) -> TypeId {
+        match &mut stmt.stmt_type {
+            StmtType::Let(stmt_let) => self.analyze_stmt_let(stmt_let),
+            StmtType::Assign(assign) => self.analyze_stmt_assign(&stmt.pos, assign),
+            StmtType::Expr(stmt_expr) => {
+                let type_id = self.analyze_expression(&mut stmt_expr.expr, None);
+                if type_id == TypeId::NEVER { TypeId::NEVER } else { TypeId::VOID }
+            }
+            StmtType::Fn(stmt_fn) => {
+                self.error(&stmt_fn.name_pos, "Functions can only be declared at the top level.".to_string());
+                TypeId::VOID
+            }
+            StmtType::Return(stmt_return) => self.analyze_stmt_return(&stmt.pos, stmt_return),
+            StmtType::Break(stmt_break) => self.analyze_stmt_break(&stmt.pos, stmt_break),
+            StmtType::Continue(stmt_continue) => self.analyze_stmt_continue(&stmt.pos, stmt_continue),
+        }
+    }
+
+    /// Find the loop targeted by `break` or `continue`, the innermost one if there is no label.
+    fn find_loop(&mut self, pos: &Position, label: Option<Symbol>, keyword: &str) -> Option<usize> {
+        let loop_index = match label {
+            Some(label) => self.loops.iter().rposition(|loop_ctx| loop_ctx.label == Some(label)),
+            None => self.loops.len().checked_sub(1),
+        };
+        if loop_index.is_none() {
+            let msg = match label {
+                Some(label) => format!("Undefined label '{}.", self.symbols.get_name(label)),
+                None => format!("Cannot use '{}' outside of a loop.", keyword),
+            };
+            self.error(pos, msg);
+        }
+        loop_index
+    }
+
+    fn analyze_stmt_break(&mut self, pos: &Position, stmt_break: &mut StmtBreak) -> TypeId {
+        let Some(loop_index) = self.find_loop(pos, stmt_break.label, "break") else {
+            if let Some(value) = &mut stmt_break.value {
+                self.analyze_expression(value, None);
+            }
+            return TypeId::NEVER;
+        };
+
+        let (value_ty, value_pos) = match &mut stmt_break.value {
+            Some(value) => {
+                if !self.loops[loop_index].allow_break_value {
+                    self.error(&value.pos, "Cannot break with a value from a 'while' loop.".to_string());
+                }
+                let expected = self.loops[loop_index].break_type;
+                let value_ty = self.analyze_expression(value, expected);
+                if value_ty == TypeId::NEVER {
+                    return TypeId::NEVER;
+                }
+                (value_ty, &value.pos)
+            }
+            None => (TypeId::VOID, pos),
+        };
+
+        match self.loops[loop_index].break_type {
+            Some(break_type) if break_type != TypeId::ERROR && break_type != value_ty => {
+                let value_pos = value_pos.clone();
+                self.type_mismatch(&value_pos, break_type, value_ty);
+            }
+            _ => self.loops[loop_index].break_type = Some(value_ty),
+        }
+        TypeId::NEVER
+    }
+
+    fn analyze_stmt_continue(&mut self, pos: &Position, stmt_continue: &StmtContinue) -> TypeId {
+        self.find_loop(pos, stmt_continue.label, "continue");
+        TypeId::NEVER
+    }
+
+    fn analyze_stmt_return(&mut self, pos: &Position, stmt_return: &mut StmtReturn) -> TypeId {
+        let return_type = self.current_return_type;
+        if return_type.is_none() {
+            self.error(pos, "Cannot return outside of a function.".to_string());
+        }
+
+        match (&mut stmt_return.value, return_type) {
+            (Some(value), Some(return_type)) => {
+                let value_ty = self.analyze_expression(value, Some(return_type));
+                if value_ty != TypeId::NEVER && value_ty != return_type {
+                    self.type_mismatch(&value.pos, return_type, value_ty);
+                }
+            }
+            (Some(value), None) => {
+                self.analyze_expression(value, None);
+            }
+            (None, Some(return_type)) if return_type != TypeId::VOID && return_type != TypeId::ERROR => {
+                let msg = format!("Expected a return value of type '{}'.", self.type_interner.to_data_type[&return_type]);
+                self.error(pos, msg);
+            }
+            (None, _) => (),
+        }
+        TypeId::NEVER
+    }
+
+    fn analyze_stmt_let(&mut self, stmt_let: &mut StmtLet) -> TypeId {
+        let declared_ty = stmt_let.var_type.as_mut().map(|var_type| self.resolve_type(var_type));
+
+        let init_ty = self.analyze_expression(&mut stmt_let.init, declared_ty);
+        let mut var_ty = init_ty;
+        if let Some(declared_ty) = declared_ty {
+            if declared_ty != init_ty {
+                self.type_mismatch(&stmt_let.init.pos, declared_ty, init_ty);
+            }
+            var_ty = declared_ty;
+        }
+        if init_ty == TypeId::VOID || init_ty == TypeId::NEVER {
+            let msg = format!("Cannot bind a value of type '{}' to a variable.", self.type_interner.to_data_type[&init_ty]);
+            self.error(&stmt_let.init.pos, msg);
+            var_ty = TypeId::ERROR;
+        }
+
+        self.scopes.last_mut().unwrap().insert(stmt_let.name, Variable {
+            type_id: var_ty,
+            mutable: stmt_let.mutable,
+        });
+        TypeId::VOID
+    }
+
+    fn analyze_stmt_assign(&mut self, pos: &Position, assign: &mut StmtAssign) -> TypeId {
+        let var_ty = match &mut assign.target.expr_type {
+            ExprType::Variable(target) => {
+                let name = self.symbols.get_name(target.name);
+                match self.find_variable(target.name) {
+                    Some(variable) if !variable.mutable => {
+                        let (msg, type_id) = (format!("Cannot assign twice to immutable variable '{}'.", name), variable.type_id);
+                        self.error(&assign.target.pos, msg);
+                        type_id
+                    }
+                    Some(variable) => variable.type_id,
+                    None => {
+                        let msg = format!("Undefined variable '{}'.", name);
+                        self.undefined(&assign.target.pos, msg);
+                        TypeId::ERROR
+                    }
+                }
+            }
+            ExprType::Deref(deref) => {
+                let target_ty = self.analyze_expr_deref(deref);
+                if let Some((_, false)) = self.type_interner.ref_target(deref.expr.type_id) {
+                    self.error(&assign.target.pos, "Cannot assign through a shared reference.".to_string());
+                }
+                target_ty
+            }
+            _ => {
+                self.error(&assign.target.pos, "Invalid assignment target.".to_string());
+                self.analyze_expression(&mut assign.value, None);
+                return TypeId::VOID;
+            }
+        };
+        assign.target.type_id = var_ty;
+
+        let value_ty = self.analyze_expression(&mut assign.value, Some(var_ty));
+        let value_ty = match assign.ope {
+            Some(ope) => self.binary_result_type(pos, ope, var_ty, value_ty),
+            None => value_ty,
+        };
+        if value_ty != var_ty {
+            self.type_mismatch(&assign.value.pos, var_ty, value_ty);
+        }
+        TypeId::VOID
+    }
+
+    /// Analyze an expression and store its type in it, where `expected` is the type wanted by the context and decides the type of untagged literals.
+    fn analyze_expression(&mut self, expr: &mut Expr, expected: Option<TypeId>) -> TypeId {
+        let type_id = match &mut expr.expr_type {
+            ExprType::Literal(literal) => self.analyze_expr_literal(&expr.pos, literal, expected),
+            ExprType::Unary(unary) => self.analyze_expr_unary(&expr.pos, unary, expected),
+            ExprType::Binary(binary) => self.analyze_expr_binary(&expr.pos, binary, expected),
+            ExprType::Variable(variable) => self.analyze_expr_variable(&expr.pos, variable),
+            ExprType::Block(block) => self.analyze_expr_block(block, expected),
+            ExprType::Call(call) => self.analyze_expr_call(&expr.pos, call),
+            ExprType::If(expr_if) => self.analyze_expr_if(expr_if, expected),
+            ExprType::While(expr_while) => self.analyze_expr_while(expr_while),
+            ExprType::Loop(expr_loop) => self.analyze_expr_loop(expr_loop),
+            ExprType::Cast(cast) => self.analyze_expr_cast(&expr.pos, cast),
+            ExprType::Borrow(borrow) => self.analyze_expr_borrow(borrow),
+            ExprType::Deref(deref) => self.analyze_expr_deref(deref),
+            ExprType::Error => TypeId::ERROR,
+        };
+        expr.type_id = type_id;
+        type_id
+    }
+
+    /// Analyze an expression that must be a `bool`, such as the condition of `if` or `while`.
+    fn analyze_condition(&mut self, cond: &mut Expr) {
+        let cond_ty = self.analyze_expression(cond, Some(TypeId::BOOL));
+        if cond_ty != TypeId::BOOL {
+            self.type_mismatch(&cond.pos, TypeId::BOOL, cond_ty);
+        }
+    }
+
+    fn analyze_expr_if(&mut self, expr_if: &mut ExprIf, expected: Option<TypeId>) -> TypeId {
+        self.analyze_condition(&mut expr_if.cond);
+
+        let then_ty = self.analyze_expression(&mut expr_if.then_branch, expected);
+        let else_ty = match &mut expr_if.else_branch {
+            Some(else_branch) => {
+                let expected = if then_ty == TypeId::NEVER { expected } else { Some(then_ty) };
+                self.analyze_expression(else_branch, expected)
+            }
+            None => TypeId::VOID,
+        };
+
+        match &expr_if.else_branch {
+            _ if then_ty == TypeId::ERROR || else_ty == TypeId::ERROR => TypeId::ERROR,
+            None if then_ty != TypeId::VOID && then_ty != TypeId::NEVER => {
+                self.type_mismatch(&expr_if.then_branch.pos, TypeId::VOID, then_ty);
+                TypeId::ERROR
+            }
+            None => TypeId::VOID,
+            Some(_) if then_ty == TypeId::NEVER => else_ty,
+            Some(_) if else_ty == TypeId::NEVER || else_ty == then_ty => then_ty,
+            Some(else_branch) => {
+                let pos = else_branch.pos.clone();
+                self.type_mismatch(&pos, then_ty, else_ty);
+                TypeId::ERROR
+            }
+        }
+    }
+
+    fn analyze_expr_while(&mut self, expr_while: &mut ExprWhile) -> TypeId {
+        self.analyze_condition(&mut expr_while.cond);
+
+        self.loops.push(LoopContext {
+            label: expr_while.label,
+            allow_break_value: false,
+            break_type: None,
+        });
+        let body_ty = self.analyze_expression(&mut expr_while.body, None);
+        self.loops.pop();
+        if body_ty != TypeId::VOID && body_ty != TypeId::NEVER {
+            self.type_mismatch(&expr_while.body.pos, TypeId::VOID, body_ty);
+        }
+        TypeId::VOID
+    }
+
+    fn analyze_expr_loop(&mut self, expr_loop: &mut ExprLoop) -> TypeId {
+        self.loops.push(LoopContext {
+            label: expr_loop.label,
+            allow_break_value: true,
+            break_type: None,
+        });
+        let body_ty = self.analyze_expression(&mut expr_loop.body, None);
+        let loop_ctx = self.loops.pop().unwrap();
+        if body_ty != TypeId::VOID && body_ty != TypeId::NEVER {
+            self.type_mismatch(&expr_loop.body.pos, TypeId::VOID, body_ty);
+        }
+
+        // A loop without any `break` never finishes.
+        loop_ctx.break_type.unwrap_or(TypeId::NEVER)
+    }
+
+    fn analyze_expr_block(&mut self, block: &mut ExprBlock, expected: Option<TypeId>) -> TypeId {
+        self.scopes.push(HashMap::new());
+
+        let mut diverges = false;
+        for stmt in &mut block.stmts {
+            if self.analyze_statement(stmt) == TypeId::NEVER {
+                diverges = true;
+            }
+        }
+        let result = match &mut block.tail {
+            Some(tail) => self.analyze_expression(tail, expected),
+            // The statement that failed to parse may have been meant as the tail.
+            None if block.stmts.last().is_some_and(Self::is_error_stmt) => TypeId::ERROR,
+            None if diverges => TypeId::NEVER,
+            None => TypeId::VOID,
+        };
+
+        self.scopes.pop();
+        result
+    }
+
+    /// Whether a statement stands for one that failed to parse.
+    fn is_error_stmt(stmt: &Stmt) -> bool {
+        matches!(&stmt.stmt_type, StmtType::Expr(stmt_expr) if matches!(stmt_expr.expr.expr_type, ExprType::Error))
+    }
+
+    fn analyze_expr_call(&mut self, pos: &Position, call: &mut ExprCall) -> TypeId {
+        let signature = match &call.callee.expr_type {
+            ExprType::Variable(callee) => match self.functions.get(&callee.name) {
+                Some(function) => Some((function.param_types.clone(), function.return_type)),
+                None => {
+                    let msg = format!("Undefined function '{}'.", self.symbols.get_name(callee.name));
+                    self.undefined(&call.callee.pos, msg);
+                    None
+                }
+            },
+            _ => {
+                self.error(&call.callee.pos, "Only functions can be called.".to_string());
+                None
+            }
+        };
+        let Some((param_types, return_type)) = signature else {
+            for arg in &mut call.args {
+                self.analyze_expression(arg, None);
+            }
+            return TypeId::ERROR;
+        };
+
+        if call.args.len() != param_types.len() {
+            self.error(pos, format!("Expected {} arguments, but found {}.", param_types.len(), call.args.len()));
+        }
+        for (i, arg) in call.args.iter_mut().enumerate() {
+            let param_type = param_types.get(i).copied();
+            let arg_ty = self.analyze_expression(arg, param_type);
+            if let Some(param_type) = param_type && arg_ty != param_type {
+                self.type_mismatch(&arg.pos, param_type, arg_ty);
+            }
+        }
+        return_type
+    }
+
+    fn analyze_expr_cast(&mut self, pos: &Position, cast: &mut ExprCast) -> TypeId {
+        let src_ty = self.analyze_expression(&mut cast.expr, None);
+        let dst_ty = self.resolve_type(&mut cast.target);
+        if src_ty == TypeId::ERROR || dst_ty == TypeId::ERROR || src_ty == dst_ty {
+            return dst_ty;
+        }
+
+        let numeric = |type_id| matches!(type_id,
+            TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 | TypeId::UINT32 |
+            TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 | TypeId::FLOAT32 | TypeId::FLOAT64);
+        let valid = match (src_ty, dst_ty) {
+            // Other integers may not hold a valid Unicode scalar value.
+            (_, TypeId::CHAR) => src_ty == TypeId::UINT8,
+            (TypeId::CHAR, _) => numeric(dst_ty) && !matches!(dst_ty, TypeId::FLOAT32 | TypeId::FLOAT64),
+            _ => (numeric(src_ty) || src_ty == TypeId::BOOL) && numeric(dst_ty),
+        };
+        if !valid {
+            let msg = format!("Cannot cast type '{}' to '{}'.", self.type_interner.to_data_type[&src_ty], self.type_interner.to_data_type[&dst_ty]);
+            self.error(pos, msg);
+        }
+        dst_ty
+    }
+
+    fn analyze_expr_borrow(&mut self, borrow: &mut ExprBorrow) -> TypeId {
+        let ExprType::Variable(variable) = &borrow.expr.expr_type else {
+            self.error(&borrow.expr.pos, "Can only borrow a variable.".to_string());
+            self.analyze_expression(&mut borrow.expr, None);
+            return TypeId::ERROR;
+        };
+        if let Some(var) = self.find_variable(variable.name) && borrow.mutable && !var.mutable {
+            let msg = format!("Cannot borrow immutable variable '{}' as mutable.", self.symbols.get_name(variable.name));
+            self.error(&borrow.expr.pos, msg);
+        }
+
+        match self.analyze_expression(&mut borrow.expr, None) {
+            TypeId::ERROR => TypeId::ERROR,
+            target_ty => self.type_interner.ref_type(target_ty, borrow.mutable),
+        }
+    }
+
+    fn analyze_expr_deref(&mut self, deref: &mut ExprDeref) -> TypeId {
+        let ref_ty = self.analyze_expression(&mut deref.expr, None);
+        if ref_ty == TypeId::ERROR {
+            return TypeId::ERROR;
+        }
+        match self.type_interner.ref_target(ref_ty) {
+            Some((target_ty, _)) => target_ty,
+            None => {
+                let msg = format!("Cannot dereference a value of type '{}'.", self.type_interner.to_data_type[&ref_ty]);
+                self.error(&deref.expr.pos, msg);
+                TypeId::ERROR
+            }
+        }
+    }
+
+    fn analyze_expr_variable(&mut self, pos: &Position, variable: &ExprVariable) -> TypeId {
+        match self.find_variable(variable.name) {
+            Some(var) => var.type_id,
+            None => {
+                let msg = format!("Undefined variable '{}'.", self.symbols.get_name(variable.name));
+                self.undefined(pos, msg);
+                TypeId::ERROR
+            }
+        }
+    }
+
+    /// The pseudo-type of an expression made only of untagged literals, whose type is decided by its context.
+    fn literal_type(expr: &Expr) -> Option<TypeId> {
+        match &expr.expr_type {
+            ExprType::Literal(ExprLiteral { value: Value::Integer(ValueInteger::Untyped(_)) }) => Some(TypeId::INT_LITERAL),
+            ExprType::Literal(ExprLiteral { value: Value::Float(ValueFloat::Untyped(_)) }) => Some(TypeId::FLOAT_LITERAL),
+            ExprType::Unary(unary) if !matches!(unary.ope, UnaryOperator::Not) => Self::literal_type(&unary.rhs),
+            ExprType::Binary(binary) if !Self::is_comparison(binary.ope) && !matches!(binary.ope, BinaryOperator::And | BinaryOperator::Or) => {
+                match (Self::literal_type(&binary.lhs), Self::literal_type(&binary.rhs)) {
+                    (Some(lhs_ty), Some(rhs_ty)) if lhs_ty == rhs_ty => Some(lhs_ty),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn is_comparison(ope: BinaryOperator) -> bool {
+        matches!(ope, BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::Less | BinaryOperator::LessEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual)
+    }
+
+    /// Decide the type of an untagged literal from the type wanted by its context, falling back to `int32` or `float64`.
+    fn resolve_literal_type(literal_ty: TypeId, expected: Option<TypeId>) -> TypeId {
+        match (literal_ty, expected) {
+            (TypeId::INT_LITERAL, Some(expected @ (TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
+                TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128))) => expected,
+            (TypeId::INT_LITERAL, _) => TypeId::INT32,
+            (_, Some(expected @ (TypeId::FLOAT32 | TypeId::FLOAT64))) => expected,
+            (_, _) => TypeId::FLOAT64,
+        }
+    }
+
+    fn int_bit_width(type_id: TypeId) -> u32 {
+        match type_id {
+            TypeId::INT8 | TypeId::UINT8 => 8,
+            TypeId::INT16 | TypeId::UINT16 => 16,
+            TypeId::INT32 | TypeId::UINT32 => 32,
+            TypeId::INT64 | TypeId::UINT64 => 64,
+            TypeId::INT128 | TypeId::UINT128 => 128,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Check that `-magnitude` or `magnitude` fits in the integer type `type_id`.
+    fn check_int_literal(&mut self, pos: &Position, magnitude: u128, negative: bool, type_id: TypeId) {
+        let bit_width = Self::int_bit_width(type_id);
+        let signed = matches!(type_id, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128);
+        let max = match (signed, negative) {
+            (true, true) => 1u128 << (bit_width - 1),
+            (true, false) => (1u128 << (bit_width - 1)) - 1,
+            (false, _) => u128::MAX >> (128 - bit_width),
+        };
+        if magnitude > max {
+            let literal = format!("{}{}", if negative { "-" } else { "" }, magnitude);
+            let msg = format!("The literal '{}' is out of range for type '{}'.", literal, self.type_interner.to_data_type[&type_id]);
+            self.error(pos, msg);
+        }
+    }
+
+    fn analyze_expr_literal(&mut self, pos: &Position, literal: &ExprLiteral, expected: Option<TypeId>) -> TypeId {
+        match &literal.value {
+            Value::Integer(integer) => match integer {
+                ValueInteger::Int8(_) => TypeId::INT8,
+                ValueInteger::UInt8(_) => TypeId::UINT8,
+                ValueInteger::Int16(_) => TypeId::INT16,
+                ValueInteger::UInt16(_) => TypeId::UINT16,
+                ValueInteger::Int32(_) => TypeId::INT32,
+                ValueInteger::UInt32(_) => TypeId::UINT32,
+                ValueInteger::Int64(_) => TypeId::INT64,
+                ValueInteger::UInt64(_) => TypeId::UINT64,
+                ValueInteger::Int128(_) => TypeId::INT128,
+                ValueInteger::UInt128(_) => TypeId::UINT128,
+                ValueInteger::Untyped(int) => {
+                    let type_id = Self::resolve_literal_type(TypeId::INT_LITERAL, expected);
+                    self.check_int_literal(pos, *int, false, type_id);
+                    type_id
+                }
+            },
+            Value::Float(float) => match float {
+                ValueFloat::Float32(_) => TypeId::FLOAT32,
+                ValueFloat::Float64(_) => TypeId::FLOAT64,
+                ValueFloat::Untyped(float) => {
+                    let type_id = Self::resolve_literal_type(TypeId::FLOAT_LITERAL, expected);
+                    if type_id == TypeId::FLOAT32 && float.is_finite() && !(*float as f32).is_finite() {
+                        self.error(pos, format!("The literal '{}' is out of range for type 'float32'.", float));
+                    }
+                    type_id
+                }
+            },
+            Value::Bool(_) => TypeId::BOOL,
+            Value::Char(_) => TypeId::CHAR,
+            Value::Str(_) => TypeId::STR,
+        }
+    }
+
+    fn analyze_expr_unary(&mut self, pos: &Position, unary: &mut ExprUnary, expected: Option<TypeId>) -> TypeId {
+        // A negative literal is checked as a whole, so that `-128` fits in `int8`.
+        if let (UnaryOperator::Minus, ExprType::Literal(ExprLiteral { value: Value::Integer(ValueInteger::Untyped(int)) })) = (unary.ope, &unary.rhs.expr_type) {
+            let type_id = Self::resolve_literal_type(TypeId::INT_LITERAL, expected);
+            if matches!(type_id, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128) {
+                self.check_int_literal(pos, *int, true, type_id);
+                unary.rhs.type_id = type_id;
+                return type_id;
+            }
+        }
+
+        let rhs_ty = self.analyze_expression(&mut unary.rhs, expected);
+        let supported = match unary.ope {
+            UnaryOperator::Plus => matches!(rhs_ty,
+                TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 | TypeId::UINT32 |
+                TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 | TypeId::FLOAT32 | TypeId::FLOAT64),
+            UnaryOperator::Minus => {
+                if !matches!(rhs_ty, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 | TypeId::FLOAT32 | TypeId::FLOAT64 | TypeId::ERROR) {
+                    let msg = format!("Cannot use a negative sign on type '{}'.", self.type_interner.to_data_type[&rhs_ty]);
+                    self.error(pos, msg);
+                    return TypeId::ERROR;
+                }
+                true
+            }
+            UnaryOperator::Not => rhs_ty == TypeId::BOOL,
+            UnaryOperator::BitNot => matches!(rhs_ty,
+                TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
+                TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128),
+        };
+
+        if rhs_ty == TypeId::ERROR {
+            TypeId::ERROR
+        } else if supported {
+            rhs_ty
+        } else {
+            self.unsupported_operator(pos, unary.ope, rhs_ty);
+            TypeId::ERROR
+        }
+    }
+
+    fn analyze_expr_binary(&mut self, pos: &Position, binary: &mut ExprBinary, expected: Option<TypeId>) -> TypeId {
+        if let BinaryOperator::And | BinaryOperator::Or = binary.ope {
+            self.analyze_condition(&mut binary.lhs);
+            self.analyze_condition(&mut binary.rhs);
+            return TypeId::BOOL;
+        }
+
+        // The operands of a comparison do not share the type of its result.
+        let expected = if Self::is_comparison(binary.ope) { None } else { expected };
+        // An operand made only of untagged literals takes the type of the other one, so that one is analyzed first.
+        let (lhs_ty, rhs_ty) = if Self::literal_type(&binary.lhs).is_some() && Self::literal_type(&binary.rhs).is_none() {
+            let rhs_ty = self.analyze_expression(&mut binary.rhs, expected);
+            (self.analyze_expression(&mut binary.lhs, Some(rhs_ty)), rhs_ty)
+        } else {
+            let lhs_ty = self.analyze_expression(&mut binary.lhs, expected);
+            (lhs_ty, self.analyze_expression(&mut binary.rhs, Some(lhs_ty)))
+        };
+
+        self.binary_result_type(pos, binary.ope, lhs_ty, rhs_ty)
+    }
+
+    /// The type of a binary operation on operands of the given types, reporting an error if the operator cannot be used.
+    fn binary_result_type(&mut self, pos: &Position, ope: BinaryOperator, lhs_ty: TypeId, rhs_ty: TypeId) -> TypeId {
+        if lhs_ty == TypeId::ERROR || rhs_ty == TypeId::ERROR {
+            return TypeId::ERROR;
+        }
+        if lhs_ty != rhs_ty {
+            let msg = format!("Expected the same types, but found '{}' and '{}'.", self.type_interner.to_data_type[&lhs_ty], self.type_interner.to_data_type[&rhs_ty]);
+            self.error(pos, msg);
+            return TypeId::ERROR;
+        }
+
+        let result_ty = match ope {
+            BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply |
+            BinaryOperator::Divide | BinaryOperator::Remainder | BinaryOperator::Power => {
+                match lhs_ty {
+                    TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 | TypeId::UINT32 |
+                    TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 | TypeId::FLOAT32 | TypeId::FLOAT64 => Some(lhs_ty),
+                    _ => None,
+                }
+            }
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::Less |
+            BinaryOperator::LessEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual => {
+                match lhs_ty {
+                    TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 | TypeId::UINT32 |
+                    TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 | TypeId::FLOAT32 | TypeId::FLOAT64 | TypeId::CHAR => Some(TypeId::BOOL),
+                    TypeId::BOOL if matches!(ope, BinaryOperator::Equal | BinaryOperator::NotEqual) => Some(TypeId::BOOL),
+                    _ => None,
+                }
+            }
+            BinaryOperator::BitAnd | BinaryOperator::BitOr | BinaryOperator::BitXor => {
+                match lhs_ty {
+                    TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
+                    TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 | TypeId::BOOL => Some(lhs_ty),
+                    _ => None,
+                }
+            }
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                match lhs_ty {
+                    TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
+                    TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 => Some(lhs_ty),
+                    _ => None,
+                }
+            }
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("Logical operators are analyzed by 'analyze_expr_binary'."),
+        };
+
+        result_ty.unwrap_or_else(|| {
+            self.unsupported_operator(pos, ope, lhs_ty);
+            TypeId::ERROR
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::analyzer::borrowck::BorrowChecker;
+    use crate::args::ArgContext;
+    use crate::mir::lower::MirBuilder;
+    use crate::parser::Parser;
+
+    /// Parse and analyze `source`, returning the annotated program and the interners, or the errors.
+    pub fn analyze(source: &str) -> Result<(Program, SymbolInterner, TypeInterner), Vec<CompileError>> {
+        let source = source.to_string();
+        let arg_context = ArgContext { option_args: vec![], file_name: None };
+        let mut parser = Parser::new(&source, &arg_context, SymbolInterner::create());
+        let (mut program, syntax_errors) = parser.parse();
+        if let Some(err) = syntax_errors.list.first() {
+            panic!("unexpected syntax error: {}", err.msg);
+        }
+        let mut analyzer = Analyzer::new(parser.into_symbols(), TypeInterner::create());
+        analyzer.analyze(&mut program, false).map_err(|errors| errors.list)?;
+        let (symbols, type_interner) = analyzer.into_parts();
+        Ok((program, symbols, type_interner))
+    }
+
+    /// Analyze `source`, returning the messages of the errors.
+    pub fn check(source: &str) -> Vec<String> {
+        match analyze(source) {
+            Ok(_) => vec![],
+            Err(errors) => errors.into_iter().map(|err| err.msg).collect(),
+        }
+    }
+
+    /// Analyze `source` and check its borrows, returning the errors with the lines they and their notes point at.
+    pub fn check_lines(source: &str) -> Vec<(usize, String, Vec<usize>)> {
+        let errors = match analyze(source) {
+            Ok((program, symbols, type_interner)) => {
+                let mir = MirBuilder::new(&type_interner).build(&program);
+                BorrowChecker::new(&symbols, &type_interner).check(&mir, None)
+            }
+            Err(errors) => errors,
+        };
+        errors.into_iter().map(|err| {
+            (err.pos.start_line, err.msg, err.notes.iter().map(|(pos, _)| pos.start_line).collect())
+        }).collect()
+    }
+
+    #[test]
+    fn let_type_mismatch() {
+        assert_eq!(check("let x: int64 = 1int32;"), ["Expected type 'int64', but found 'int32'."]);
+        assert_eq!(check("let x: foo = 1;"), ["Unknown type 'foo'."]);
+    }
+
+    #[test]
+    fn assign_type_mismatch() {
+        assert_eq!(check("let mut x = 1; x = 1.0;"), ["Expected type 'int32', but found 'float64'."]);
+        assert_eq!(check("let mut x = 1uint8; x += 1int32;"), ["Expected the same types, but found 'uint8' and 'int32'."]);
+    }
+
+    #[test]
+    fn assign_to_immutable_or_undefined() {
+        assert_eq!(check("let x = 1; x = 2;"), ["Cannot assign twice to immutable variable 'x'."]);
+        assert_eq!(check("y = 2;"), ["Undefined variable 'y'."]);
+        assert_eq!(check("y"), ["Undefined variable 'y'."]);
+    }
+
+    #[test]
+    fn call_arity() {
+        assert_eq!(check("fn f(a: int32) {} f();"), ["Expected 1 arguments, but found 0."]);
+        assert_eq!(check("fn f() {} f(1, 2);"), ["Expected 0 arguments, but found 2."]);
+        assert_eq!(check("g(1);"), ["Undefined function 'g'."]);
+    }
+
+    #[test]
+    fn call_argument_type() {
+        assert_eq!(check("fn f(a: int64) {} f(1int32);"), ["Expected type 'int64', but found 'int32'."]);
+    }
+
+    #[test]
+    fn return_type() {
+        assert_eq!(check("fn f() -> int32 { return 1.0; }"), ["Expected type 'int32', but found 'float64'."]);
+        assert_eq!(check("fn f() -> int32 { return; }"), ["Expected a return value of type 'int32'."]);
+        assert_eq!(check("fn f() -> int32 { 1uint8 }"), ["Expected type 'int32', but found 'uint8'."]);
+        assert_eq!(check("return;"), ["Cannot return outside of a function."]);
+    }
+
+    #[test]
+    fn function_definitions() {
+        assert_eq!(check("fn f() {} fn f() {}"), ["Function 'f' is already defined."]);
+        assert_eq!(check("fn main() {}"), ["The function name 'main' is reserved."]);
+        assert_eq!(check("fn f() { fn g() {} }"), ["Functions can only be declared at the top level."]);
+    }
+
+    #[test]
+    fn break_and_continue_errors() {
+        assert_eq!(check("break;"), ["Cannot use 'break' outside of a loop."]);
+        assert_eq!(check("continue;"), ["Cannot use 'continue' outside of a loop."]);
+        assert_eq!(check("loop { break 'x; }"), ["Undefined label 'x."]);
+        assert_eq!(check("while true { break 1; }"), ["Cannot break with a value from a 'while' loop."]);
+    }
+
+    #[test]
+    fn condition_type() {
+        assert_eq!(check("if 1.0 {}"), ["Expected type 'bool', but found 'float64'."]);
+    }
+
+    #[test]
+    fn logical_operators_need_bools() {
+        assert_eq!(check("1 && true"), ["Expected type 'bool', but found 'int32'."]);
+    }
+
+    #[test]
+    fn shift_amount_out_of_range() {
+        assert_eq!(check("1 << 32"), ["The shift amount must be smaller than the bit width of type 'int32' (32 bits)."]);
+        assert_eq!(check("1uint8 >> 8uint8"), ["The shift amount must be smaller than the bit width of type 'uint8' (8 bits)."]);
+        assert_eq!(check("1.0 << 1.0"), ["Cannot use operator '<<' on type 'float64'."]);
+    }
+
+    #[test]
+    fn remainder_on_bool() {
+        assert_eq!(check("true % false"), ["Cannot use operator '%' on type 'bool'."]);
+    }
+
+    #[test]
+    fn invalid_cast() {
+        assert_eq!(check("1.0 as bool"), ["Cannot cast type 'float64' to 'bool'."]);
+    }
+
+    #[test]
+    fn untagged_literal_out_of_range() {
+        assert_eq!(check("let x: uint8 = 256;"), ["The literal '256' is out of range for type 'uint8'."]);
+        assert_eq!(check("let x: uint8 = -1;"), ["Cannot use a negative sign on type 'uint8'."]);
+        assert_eq!(check("3000000000"), ["The literal '3000000000' is out of range for type 'int32'."]);
+    }
+
+    #[test]
+    fn reports_every_type_error() {
+        assert_eq!(check("let x: int64 = 1int32; let y: bool = 1.0;"), [
+            "Expected type 'int64', but found 'int32'.",
+            "Expected type 'bool', but found 'float64'.",
+        ]);
+    }
+
+    #[test]
+    fn errors_do_not_cascade() {
+        assert_eq!(check("let x = y; let z = x + 1; let w: bool = z;"), ["Undefined variable 'y'."]);
+    }
+
+    /// Analyze `source` around its syntax errors, returning the messages of the type errors.
+    fn check_partial(source: &str) -> Vec<String> {
+        let source = source.to_string();
+        let arg_context = ArgContext { option_args: vec![], file_name: None };
+        let mut parser = Parser::new(&source, &arg_context, SymbolInterner::create());
+        let (mut program, syntax_errors) = parser.parse();
+        assert!(!syntax_errors.list.is_empty());
+        let mut analyzer = Analyzer::new(parser.into_symbols(), TypeInterner::create());
+        match analyzer.analyze(&mut program, true) {
+            Ok(()) => vec![],
+            Err(errors) => errors.list.into_iter().map(|err| err.msg).collect(),
+        }
+    }
+
+    #[test]
+    fn type_errors_after_syntax_errors() {
+        assert_eq!(check_partial("let a = ; let b: bool = 1;"), ["Expected type 'bool', but found 'int32'."]);
+    }
+
+    #[test]
+    fn no_undefined_names_after_syntax_errors() {
+        assert_eq!(check_partial("let a = ; let b = c + 1; f();"), Vec::<String>::new());
+        assert_eq!(check_partial("fn f() -> int32 { let a = 1; a + }"), Vec::<String>::new());
+    }
+}
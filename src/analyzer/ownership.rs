@@ -0,0 +1,403 @@
+//! The module of `MoveChecker`, which reports uses of variables whose values were moved out of them
+
+use crate::analyzer::error::CompileError;
+use crate::ast::expr::{Expr, ExprType};
+use crate::ast::program::Program;
+use crate::ast::stmt::{Stmt, StmtFn, StmtType};
+use crate::compiler_data::data_type::TypeInterner;
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
+use std::collections::HashMap;
+
+pub struct MoveChecker<'a> {
+    symbols: &'a SymbolInterner,
+    type_interner: &'a TypeInterner,
+    /// The variables in scope, mapped to their indices in `names` and in `MoveState::moved`.
+    scopes: Vec<HashMap<Symbol, usize>>,
+    names: Vec<Symbol>,
+    /// The state at the point being checked, `None` if the point cannot be reached.
+    state: Option<MoveState>,
+    loops: Vec<LoopState>,
+    /// Whether errors are recorded, turned off while the state at the start of a loop is worked out.
+    report: bool,
+    errors: Vec<CompileError>,
+}
+
+/// Which variables have been moved at a point of the program.
+#[derive(Clone)]
+struct MoveState {
+    /// Where each variable in scope was moved, or `None` if it holds a value.
+    moved: Vec<Option<Position>>,
+}
+
+/// A loop being checked, and the states at its `break` and `continue` statements so far.
+struct LoopState {
+    label: Option<Symbol>,
+    /// The number of variables in scope at the loop.
+    var_cnt: usize,
+    breaks: Option<MoveState>,
+    continues: Option<MoveState>,
+}
+
+impl<'a> MoveChecker<'a> {
+    #[must_use]
+    pub fn new(symbols: &'a SymbolInterner, type_interner: &'a TypeInterner) -> Self {
+        Self {
+            symbols,
+            type_interner,
+            scopes: vec![],
+            names: vec![],
+            state: None,
+            loops: vec![],
+            report: true,
+            errors: vec![],
+        }
+    }
+
+    /// Check a program that passed type checking, with `globals` the moves of earlier REPL inputs, updated to its end.
+    #[must_use]
+    pub fn check(mut self, program: &Program, globals: &mut HashMap<Symbol, Option<Position>>) -> Vec<CompileError> {
+        self.enter_body();
+        for (name, moved) in globals.iter() {
+            self.declare(*name);
+            if let Some(state) = &mut self.state {
+                *state.moved.last_mut().unwrap() = moved.clone();
+            }
+        }
+        for stmt in &program.stmts {
+            if !matches!(stmt.stmt_type, StmtType::Fn(_)) {
+                self.check_statement(stmt);
+            }
+        }
+        if let Some(tail) = &program.tail {
+            self.check_expression(tail, false);
+        }
+        if let Some(state) = &self.state {
+            *globals = self.scopes[0].iter().map(|(name, index)| (*name, state.moved[*index].clone())).collect();
+        }
+
+        for stmt in &program.stmts {
+            if let StmtType::Fn(stmt_fn) = &stmt.stmt_type {
+                self.check_function(stmt_fn);
+            }
+        }
+        self.errors
+    }
+
+    /// Start checking the top level or the body of a function, where no variable is in scope yet.
+    fn enter_body(&mut self) {
+        self.scopes = vec![HashMap::new()];
+        self.names.clear();
+        self.loops.clear();
+        self.state = Some(MoveState { moved: vec![] });
+    }
+
+    fn check_function(&mut self, stmt_fn: &StmtFn) {
+        self.enter_body();
+        for param in &stmt_fn.params {
+            self.declare(param.name);
+        }
+        self.check_expression(&stmt_fn.body, true);
+    }
+
+    fn declare(&mut self, name: Symbol) {
+        let index = self.names.len();
+        self.names.push(name);
+        self.scopes.last_mut().unwrap().insert(name, index);
+        if let Some(state) = &mut self.state {
+            state.moved.resize(index, None);
+            state.moved.push(None);
+        }
+    }
+
+    fn find_variable(&self, name: Symbol) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&name).copied())
+    }
+
+    /// Merge the state of another path into `state`, where a variable is moved if it is moved on either path.
+    fn join(state: &mut Option<MoveState>, other: Option<MoveState>) {
+        match (state, other) {
+            (state @ None, other) => *state = other,
+            (Some(_), None) => (),
+            (Some(state), Some(other)) => {
+                state.moved.truncate(other.moved.len());
+                for (moved, other_moved) in state.moved.iter_mut().zip(other.moved) {
+                    if moved.is_none() {
+                        *moved = other_moved;
+                    }
+                }
+            }
+        }
+    }
+
+    fn same_moves(state: &Option<MoveState>, other: &Option<MoveState>) -> bool {
+        match (state, other) {
+            (None, None) => true,
+            (Some(state), Some(other)) => state.moved.iter().map(Option::is_some).eq(other.moved.iter().map(Option::is_some)),
+            _ => false,
+        }
+    }
+
+    /// Take the current state for a jump out of the scopes deeper than `var_cnt` variables, leaving the current point unreachable.
+    fn take_state_to(&mut self, var_cnt: usize) -> Option<MoveState> {
+        let mut state = self.state.take();
+        if let Some(state) = &mut state {
+            state.moved.truncate(var_cnt);
+        }
+        state
+    }
+
+    /// Check a use of a variable, which moves its value out of it if `moves` is set.
+    fn use_variable(&mut self, pos: &Position, name: Symbol, moves: bool) {
+        let Some(index) = self.find_variable(name) else { return };
+        let Some(state) = &mut self.state else { return };
+
+        if let Some(move_pos) = &state.moved[index] {
+            if self.report {
+                let msg = format!("Use of moved value '{}'.", self.symbols.get_name(name));
+                let note = if move_pos == pos {
+                    "The value was moved here, in a previous iteration of the loop."
+                } else {
+                    "The value was moved here."
+                };
+                let error = CompileError::new(pos.clone(), msg).with_note(move_pos.clone(), note.to_string());
+                self.errors.push(error);
+            }
+        } else if moves {
+            state.moved[index] = Some(pos.clone());
+        }
+    }
+
+    fn check_statement(&mut self, stmt: &Stmt) {
+        match &stmt.stmt_type {
+            StmtType::Let(stmt_let) => {
+                self.check_expression(&stmt_let.init, true);
+                self.declare(stmt_let.name);
+            }
+            StmtType::Assign(assign) => {
+                self.check_expression(&assign.value, true);
+                let target = match &assign.target.expr_type {
+                    ExprType::Variable(target) => target,
+                    ExprType::Deref(deref) => {
+                        self.check_expression(&deref.expr, false);
+                        return;
+                    }
+                    _ => unreachable!(),
+                };
+                if assign.ope.is_some() {
+                    self.use_variable(&assign.target.pos, target.name, false);
+                } else if let (Some(index), Some(state)) = (self.find_variable(target.name), &mut self.state) {
+                    // A new value is given to the variable, whether it was moved or not.
+                    state.moved[index] = None;
+                }
+            }
+            // The value of an expression statement is dropped without a new owner, so it is only read.
+            StmtType::Expr(stmt_expr) => self.check_expression(&stmt_expr.expr, false),
+            StmtType::Fn(_) => (),
+            StmtType::Return(stmt_return) => {
+                if let Some(value) = &stmt_return.value {
+                    self.check_expression(value, true);
+                }
+                self.state = None;
+            }
+            StmtType::Break(stmt_break) => {
+                if let Some(value) = &stmt_break.value {
+                    self.check_expression(value, true);
+                }
+                if let Some(loop_index) = self.find_loop(stmt_break.label) {
+                    let state = self.take_state_to(self.loops[loop_index].var_cnt);
+                    Self::join(&mut self.loops[loop_index].breaks, state);
+                }
+                self.state = None;
+            }
+            StmtType::Continue(stmt_continue) => {
+                if let Some(loop_index) = self.find_loop(stmt_continue.label) {
+                    let state = self.take_state_to(self.loops[loop_index].var_cnt);
+                    Self::join(&mut self.loops[loop_index].continues, state);
+                }
+                self.state = None;
+            }
+        }
+    }
+
+    fn find_loop(&self, label: Option<Symbol>) -> Option<usize> {
+        match label {
+            Some(label) => self.loops.iter().rposition(|loop_state| loop_state.label == Some(label)),
+            None => self.loops.len().checked_sub(1),
+        }
+    }
+
+    /// Check an expression, where `consumes` tells whether its value is taken by the context rather than only read.
+    fn check_expression(&mut self, expr: &Expr, consumes: bool) {
+        match &expr.expr_type {
+            ExprType::Literal(_) | ExprType::Error => (),
+            ExprType::Unary(unary) => self.check_expression(&unary.rhs, false),
+            ExprType::Binary(binary) => {
+                self.check_expression(&binary.lhs, false);
+                self.check_expression(&binary.rhs, false);
+            }
+            ExprType::Variable(variable) => self.use_variable(&expr.pos, variable.name, consumes && !self.type_interner.is_copy(expr.type_id)),
+            ExprType::Block(block) => {
+                let var_cnt = self.names.len();
+                self.scopes.push(HashMap::new());
+                for stmt in &block.stmts {
+                    self.check_statement(stmt);
+                }
+                if let Some(tail) = &block.tail {
+                    self.check_expression(tail, consumes);
+                }
+                self.scopes.pop();
+                self.names.truncate(var_cnt);
+                if let Some(state) = &mut self.state {
+                    state.moved.truncate(var_cnt);
+                }
+            }
+            ExprType::Call(call) => {
+                for arg in &call.args {
+                    self.check_expression(arg, true);
+                }
+            }
+            ExprType::If(expr_if) => {
+                self.check_expression(&expr_if.cond, false);
+                let else_state = self.state.clone();
+                self.check_expression(&expr_if.then_branch, consumes);
+                let then_state = std::mem::replace(&mut self.state, else_state);
+                if let Some(else_branch) = &expr_if.else_branch {
+                    self.check_expression(else_branch, consumes);
+                }
+                Self::join(&mut self.state, then_state);
+            }
+            ExprType::While(expr_while) => self.check_loop(expr_while.label, Some(&expr_while.cond), &expr_while.body),
+            ExprType::Loop(expr_loop) => self.check_loop(expr_loop.label, None, &expr_loop.body),
+            ExprType::Cast(cast) => self.check_expression(&cast.expr, false),
+            ExprType::Borrow(borrow) => self.check_expression(&borrow.expr, false),
+            ExprType::Deref(deref) => {
+                self.check_expression(&deref.expr, false);
+                if consumes && !self.type_interner.is_copy(expr.type_id) && self.state.is_some() && self.report {
+                    let msg = format!("Cannot move a value of type '{}' out of a reference.", self.type_interner.to_data_type[&expr.type_id]);
+                    self.errors.push(CompileError::new(expr.pos.clone(), msg));
+                }
+            }
+        }
+    }
+
+    /// Check a loop, whose body may run again with the variables moved by its previous iterations.
+    fn check_loop(&mut self, label: Option<Symbol>, cond: Option<&Expr>, body: &Expr) {
+        let entry = self.state.clone();
+
+        // Find the state at the start of an iteration first, by going through the body until it settles.
+        let report = std::mem::replace(&mut self.report, false);
+        let mut head = entry.clone();
+        loop {
+            let (_, back) = self.check_iteration(label, cond, body, head.clone());
+            let mut next = entry.clone();
+            Self::join(&mut next, back);
+            if Self::same_moves(&next, &head) {
+                break;
+            }
+            head = next;
+        }
+        self.report = report;
+
+        let (exit, _) = self.check_iteration(label, cond, body, head);
+        self.state = exit;
+    }
+
+    /// Check one loop iteration from `head`, giving back the states after the loop and at the next iteration.
+    fn check_iteration(&mut self, label: Option<Symbol>, cond: Option<&Expr>, body: &Expr, head: Option<MoveState>) -> (Option<MoveState>, Option<MoveState>) {
+        self.state = head;
+        let mut exit = None;
+        if let Some(cond) = cond {
+            self.check_expression(cond, false);
+            exit = self.state.clone();
+        }
+
+        self.loops.push(LoopState {
+            label,
+            var_cnt: self.names.len(),
+            breaks: None,
+            continues: None,
+        });
+        self.check_expression(body, false);
+        let loop_state = self.loops.pop().unwrap();
+
+        let mut back = self.state.take();
+        Self::join(&mut back, loop_state.continues);
+        Self::join(&mut exit, loop_state.breaks);
+        (exit, back)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::tests::check_lines as check;
+
+    #[test]
+    fn move_in_a_previous_iteration() {
+        let errors = check("
+            let s = \"a\";
+            let mut i: int32 = 0;
+            while i < 3 {
+                let t = s;
+                i += 1;
+            }
+        ");
+        assert_eq!(errors, [(5, "Use of moved value 's'.".to_string(), vec![5])]);
+    }
+
+    #[test]
+    fn reassigned_before_the_next_iteration() {
+        let errors = check("
+            let mut s = \"a\";
+            let mut i: int32 = 0;
+            while i < 3 {
+                let t = s;
+                s = \"b\";
+                i += 1;
+            }
+            let u = s;
+        ");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn moved_before_continue() {
+        let errors = check("
+            let mut s = \"a\";
+            let mut i: int32 = 0;
+            while i < 3 {
+                i += 1;
+                if i == 2 {
+                    let t = s;
+                    continue;
+                }
+                s = \"b\";
+            }
+        ");
+        assert_eq!(errors, [(7, "Use of moved value 's'.".to_string(), vec![7])]);
+    }
+
+    #[test]
+    fn moved_before_break() {
+        let errors = check("
+            let s = \"a\";
+            loop {
+                if true { let t = s; break; }
+            }
+            let u = s;
+        ");
+        assert_eq!(errors, [(6, "Use of moved value 's'.".to_string(), vec![4])]);
+    }
+
+    #[test]
+    fn loop_left_after_the_move() {
+        let errors = check("
+            let s = \"a\";
+            loop {
+                let t = s;
+                break;
+            }
+        ");
+        assert!(errors.is_empty());
+    }
+}
@@ -9,9 +9,40 @@ pub enum OptionArg {
     Version,
     DebugPrintToken,
     DebugPrintAST,
-    EmitLLVM,
+    Emit(EmitKind),
     Output(String),
-    NoOptimize,
+    Optimize(OptLevel),
+    /// `iranta run file.ir`: run the program right away with the JIT instead of writing a file.
+    Run,
+    /// `iranta repl`: evaluate code typed line by line.
+    Repl,
+    /// `--std=PATH`: link executables against an external standard library instead of the bundled runtime.
+    Std(String),
+    /// `--overflow-checks=on|off`: whether integer arithmetic panics when it overflows instead of wrapping.
+    OverflowChecks(bool),
+}
+
+/// The kind of file written by the compiler, an executable by default.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EmitKind {
+    /// The MIR of the program as text, written before any code is generated.
+    Mir,
+    LlvmIr,
+    LlvmBc,
+    Asm,
+    Obj,
+    Exe,
+}
+
+/// The optimization level chosen with `-O`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OptLevel {
+    O0,
+    O1,
+    O2,
+    O3,
+    /// Optimize for a small code size.
+    Os,
 }
 
 impl ArgContext {
@@ -20,7 +51,8 @@ impl ArgContext {
         let mut option_args = vec![];
         let mut need_file = true;
 
-        for mut i in 0..args.len() {
+        let mut i = 0;
+        while i < args.len() {
             let arg = &args[i];
             match arg.as_str() {
                 "-v" | "--version" => {
@@ -38,11 +70,48 @@ impl ArgContext {
                         option_args.push(OptionArg::Output(output.clone()));
                     }
                 }
-                "--emit-llvm" => option_args.push(OptionArg::EmitLLVM),
-                "-O0" | "--no-optimize" => option_args.push(OptionArg::NoOptimize),
+                _ if arg.starts_with("--std=") => {
+                    let path = &arg["--std=".len()..];
+                    if path.is_empty() {
+                        return Err("No standard library specified".to_string());
+                    }
+                    option_args.push(OptionArg::Std(path.to_string()));
+                }
+                _ if arg.starts_with("--overflow-checks=") => {
+                    let enabled = match &arg["--overflow-checks=".len()..] {
+                        "on" => true,
+                        "off" => false,
+                        value => return Err(format!("Invalid value of overflow checks: {} (expect on or off)", value)),
+                    };
+                    option_args.push(OptionArg::OverflowChecks(enabled));
+                }
+                "--emit-llvm" => option_args.push(OptionArg::Emit(EmitKind::LlvmIr)),
+                _ if arg.starts_with("--emit=") => {
+                    let kind = match &arg["--emit=".len()..] {
+                        "mir" => EmitKind::Mir,
+                        "llvm-ir" => EmitKind::LlvmIr,
+                        "llvm-bc" => EmitKind::LlvmBc,
+                        "asm" => EmitKind::Asm,
+                        "obj" => EmitKind::Obj,
+                        "exe" => EmitKind::Exe,
+                        kind => return Err(format!("Invalid kind of output: {} (expect mir, llvm-ir, llvm-bc, asm, obj or exe)", kind)),
+                    };
+                    option_args.push(OptionArg::Emit(kind));
+                }
+                "-O0" | "--no-optimize" => option_args.push(OptionArg::Optimize(OptLevel::O0)),
+                "-O1" => option_args.push(OptionArg::Optimize(OptLevel::O1)),
+                "-O2" => option_args.push(OptionArg::Optimize(OptLevel::O2)),
+                "-O3" => option_args.push(OptionArg::Optimize(OptLevel::O3)),
+                "-Os" => option_args.push(OptionArg::Optimize(OptLevel::Os)),
+                "run" if i == 0 => option_args.push(OptionArg::Run),
+                "repl" if i == 0 => {
+                    option_args.push(OptionArg::Repl);
+                    need_file = false;
+                }
                 _ if arg.starts_with("-") => return Err(format!("Invalid argument: {}", arg)),
                 _ => file_name = Some(arg.clone()),
             }
+            i += 1;
         }
 
         if need_file && file_name.is_none() {
@@ -54,4 +123,52 @@ impl ArgContext {
             })
         }
     }
+
+    /// The kind of output chosen by the last `--emit`, an executable without one.
+    #[must_use]
+    pub fn emit_kind(&self) -> EmitKind {
+        self.option_args.iter().rev().find_map(|option| match option {
+            OptionArg::Emit(kind) => Some(*kind),
+            _ => None,
+        }).unwrap_or(EmitKind::Exe)
+    }
+
+    /// The optimization level chosen by the last `-O` option, if any.
+    #[must_use]
+    pub fn opt_level(&self) -> Option<OptLevel> {
+        self.option_args.iter().rev().find_map(|option| match option {
+            OptionArg::Optimize(level) => Some(*level),
+            _ => None,
+        })
+    }
+
+    /// The external standard library chosen by the last `--std` option, if any.
+    #[must_use]
+    pub fn std_path(&self) -> Option<&str> {
+        self.option_args.iter().rev().find_map(|option| match option {
+            OptionArg::Std(path) => Some(path.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Whether integer arithmetic is checked for overflow, by the last `--overflow-checks` option or else with no `-O` or `-O0`.
+    #[must_use]
+    pub fn overflow_checks(&self) -> bool {
+        self.option_args.iter().rev().find_map(|option| match option {
+            OptionArg::OverflowChecks(enabled) => Some(*enabled),
+            _ => None,
+        }).unwrap_or_else(|| matches!(self.opt_level(), None | Some(OptLevel::O0)))
+    }
+
+    /// Whether the program is run with the JIT rather than written to a file.
+    #[must_use]
+    pub fn is_run(&self) -> bool {
+        self.option_args.iter().any(|option| matches!(option, OptionArg::Run))
+    }
+
+    /// Whether the interactive REPL is started instead of compiling a file.
+    #[must_use]
+    pub fn is_repl(&self) -> bool {
+        self.option_args.iter().any(|option| matches!(option, OptionArg::Repl))
+    }
 }
@@ -2,12 +2,27 @@
 
 use std::fmt::Display;
 
+#[derive(Clone, Copy)]
 pub enum BinaryOperator {
     Plus,
     Minus,
     Multiply,
     Divide,
+    Remainder,
     Power,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
 impl Display for BinaryOperator {
@@ -18,7 +33,21 @@ impl Display for BinaryOperator {
                 Self::Minus => "-",
                 Self::Multiply => "*",
                 Self::Divide => "/",
+                Self::Remainder => "%",
                 Self::Power => "**",
+                Self::Equal => "==",
+                Self::NotEqual => "!=",
+                Self::Less => "<",
+                Self::LessEqual => "<=",
+                Self::Greater => ">",
+                Self::GreaterEqual => ">=",
+                Self::And => "&&",
+                Self::Or => "||",
+                Self::BitAnd => "&",
+                Self::BitOr => "|",
+                Self::BitXor => "^",
+                Self::ShiftLeft => "<<",
+                Self::ShiftRight => ">>",
             }
         )
     }
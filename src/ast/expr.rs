@@ -1,19 +1,36 @@
 //! The module of `Expr`
 
 use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::stmt::Stmt;
+use crate::ast::type_expr::TypeExpr;
 use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::TypeId;
 use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
 use crate::compiler_data::value::Value;
 
 pub struct Expr {
     pub pos: Position,
     pub expr_type: ExprType,
+    /// The type of the expression, filled by the analyzer and `TypeId::ERROR` before that.
+    pub type_id: TypeId,
 }
 
 pub enum ExprType {
     Literal(ExprLiteral),
     Unary(ExprUnary),
     Binary(ExprBinary),
+    Variable(ExprVariable),
+    Block(ExprBlock),
+    Call(ExprCall),
+    If(ExprIf),
+    While(ExprWhile),
+    Loop(ExprLoop),
+    Cast(ExprCast),
+    Borrow(ExprBorrow),
+    Deref(ExprDeref),
+    /// An expression or a statement that failed to parse, kept so that later passes can go on.
+    Error,
 }
 
 pub struct ExprLiteral {
@@ -30,3 +47,52 @@ pub struct ExprBinary {
     pub lhs: Box<Expr>,
     pub rhs: Box<Expr>,
 }
+
+pub struct ExprVariable {
+    pub name: Symbol,
+}
+
+/// A block of statements, whose value is the value of its tail expression or `void` without one.
+pub struct ExprBlock {
+    pub stmts: Vec<Stmt>,
+    pub tail: Option<Box<Expr>>,
+}
+
+pub struct ExprCast {
+    pub expr: Box<Expr>,
+    pub target: TypeExpr,
+}
+
+/// A borrow `&expr` or `&mut expr`, giving a reference to a variable.
+pub struct ExprBorrow {
+    pub mutable: bool,
+    pub expr: Box<Expr>,
+}
+
+/// A dereference `*expr`, giving the value a reference points to.
+pub struct ExprDeref {
+    pub expr: Box<Expr>,
+}
+
+pub struct ExprCall {
+    pub callee: Box<Expr>,
+    pub args: Vec<Expr>,
+}
+
+/// An `if` expression, where `else_branch` is either a block or another `if` expression.
+pub struct ExprIf {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Option<Box<Expr>>,
+}
+
+pub struct ExprWhile {
+    pub label: Option<Symbol>,
+    pub cond: Box<Expr>,
+    pub body: Box<Expr>,
+}
+
+pub struct ExprLoop {
+    pub label: Option<Symbol>,
+    pub body: Box<Expr>,
+}
@@ -3,3 +3,6 @@
 pub mod expr;
 pub mod unary_ope;
 pub mod binary_ope;
+pub mod stmt;
+pub mod type_expr;
+pub mod program;
@@ -0,0 +1,10 @@
+//! The module of `Program`
+
+use crate::ast::expr::Expr;
+use crate::ast::stmt::Stmt;
+
+/// A whole source file: a list of statements, optionally followed by an expression whose value is printed.
+pub struct Program {
+    pub stmts: Vec<Stmt>,
+    pub tail: Option<Box<Expr>>,
+}
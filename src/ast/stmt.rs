@@ -0,0 +1,69 @@
+//! The module of `Stmt`
+
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::expr::Expr;
+use crate::ast::type_expr::TypeExpr;
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
+
+pub struct Stmt {
+    pub pos: Position,
+    pub stmt_type: StmtType,
+}
+
+pub enum StmtType {
+    Let(StmtLet),
+    Assign(StmtAssign),
+    Expr(StmtExpr),
+    Fn(StmtFn),
+    Return(StmtReturn),
+    Break(StmtBreak),
+    Continue(StmtContinue),
+}
+
+pub struct StmtLet {
+    pub name: Symbol,
+    pub name_pos: Position,
+    pub mutable: bool,
+    pub var_type: Option<TypeExpr>,
+    pub init: Box<Expr>,
+}
+
+/// An assignment, where `ope` is the operator of a compound assignment such as `+=`.
+pub struct StmtAssign {
+    pub ope: Option<BinaryOperator>,
+    pub target: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+pub struct StmtExpr {
+    pub expr: Box<Expr>,
+}
+
+pub struct StmtFn {
+    pub name: Symbol,
+    pub name_pos: Position,
+    pub params: Vec<FnParam>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Box<Expr>,
+}
+
+pub struct FnParam {
+    pub name: Symbol,
+    pub pos: Position,
+    pub mutable: bool,
+    pub param_type: TypeExpr,
+}
+
+pub struct StmtReturn {
+    pub value: Option<Box<Expr>>,
+}
+
+pub struct StmtBreak {
+    pub label: Option<Symbol>,
+    pub value: Option<Box<Expr>>,
+}
+
+pub struct StmtContinue {
+    pub label: Option<Symbol>,
+}
@@ -0,0 +1,21 @@
+//! The module of `TypeExpr`
+
+use crate::compiler_data::data_type::TypeId;
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
+
+/// A type written in the source code, resolved to `type_id` by the analyzer.
+pub struct TypeExpr {
+    pub pos: Position,
+    pub type_expr_type: TypeExprType,
+    pub type_id: TypeId,
+}
+
+pub enum TypeExprType {
+    Named(Symbol),
+    /// A reference type `&T` or `&mut T`.
+    Ref {
+        mutable: bool,
+        target: Box<TypeExpr>,
+    },
+}
@@ -2,9 +2,12 @@
 
 use std::fmt::Display;
 
+#[derive(Clone, Copy)]
 pub enum UnaryOperator {
     Plus,
     Minus,
+    Not,
+    BitNot,
 }
 
 impl Display for UnaryOperator {
@@ -13,6 +16,8 @@ impl Display for UnaryOperator {
             match self {
                 Self::Plus => "+",
                 Self::Minus => "-",
+                Self::Not => "!",
+                Self::BitNot => "~",
             }
         )
     }
@@ -0,0 +1,233 @@
+//! The module for running compiled programs with the JIT, for `iranta run` and the REPL.
+
+use std::cell::RefCell;
+use std::io::Write;
+use inkwell::context::Context;
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::module::Linkage;
+use inkwell::targets::{InitializationConfig, Target};
+use inkwell::values::PointerValue;
+use inkwell::OptimizationLevel;
+use crate::args::{ArgContext, OptLevel};
+use crate::compiler::Compiler;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
+use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
+use crate::main_error::IrantaCompilerError;
+
+/// The bundled runtime, included as a module so that nothing has to be linked.
+#[path = "../../runtime/std.rs"]
+mod runtime;
+
+thread_local! {
+    /// The bytes of the last value passed to `iranta_repl_result`.
+    static REPL_RESULT: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// A top-level variable of a REPL input, kept in a global that later inputs link to by name.
+pub struct ReplGlobal {
+    pub name: Symbol,
+    pub type_id: TypeId,
+    pub mutable: bool,
+    pub link_name: String,
+}
+
+/// A check that failed while running a REPL input, at the position of the operation.
+pub struct RuntimePanic {
+    pub pos: Position,
+    pub msg: String,
+}
+
+impl IrantaCompilerError for RuntimePanic {
+    fn get_pos(&self) -> &Position {
+        &self.pos
+    }
+
+    fn get_msg(&self) -> &str {
+        &self.msg
+    }
+}
+
+/// A function of a REPL input, which later inputs link to by name.
+pub struct ReplFunction {
+    pub name: Symbol,
+    pub param_types: Vec<TypeId>,
+    pub return_type: TypeId,
+    pub link_name: String,
+}
+
+impl<'ctx> Compiler<'ctx> {
+    /// Run the `main` function of the compiled module and give back its return code.
+    pub fn run_jit(&self, arg_context: &ArgContext) -> Result<i32, String> {
+        self.optimize(arg_context.opt_level())?;
+        let engine = self.module.create_jit_execution_engine(Self::jit_opt_level(arg_context))
+            .map_err(|err| format!("Cannot create the JIT: {}", err))?;
+        self.map_runtime(&engine);
+
+        let main_fn = self.module.get_function("main").unwrap();
+        // SAFETY: `main` takes no arguments and every external function it may call has been mapped.
+        let code = unsafe { engine.run_function_as_main(main_fn, &[]) };
+        let _ = std::io::stdout().flush();
+        Ok(code)
+    }
+
+    /// Create the JIT of a REPL session, to which the module of every input is added.
+    pub fn create_repl_engine(llvm_ctx: &'ctx Context, arg_context: &ArgContext) -> Result<ExecutionEngine<'ctx>, String> {
+        Target::initialize_native(&InitializationConfig::default())
+            .map_err(|err| format!("Cannot initialize the native target: {}", err))?;
+        llvm_ctx.create_module("iranta_repl").create_jit_execution_engine(Self::jit_opt_level(arg_context))
+            .map_err(|err| format!("Cannot create the JIT: {}", err))
+    }
+
+    /// Add the module of a REPL input to the JIT of the session and run it, giving back its value or its panic.
+    pub fn run_repl(&self, engine: &ExecutionEngine<'ctx>, arg_context: &ArgContext) -> Result<Result<Option<Value>, RuntimePanic>, String> {
+        self.optimize(arg_context.opt_level())?;
+        engine.add_module(&self.module).map_err(|()| "Cannot add the input to the JIT.".to_string())?;
+        self.map_runtime(engine);
+        if let Some(function) = self.module.get_function("iranta_repl_result") {
+            engine.add_global_mapping(&function, iranta_repl_result as *const () as usize);
+        }
+        REPL_RESULT.with_borrow_mut(|result| *result = None);
+
+        let address = engine.get_function_address(&self.output_file).map_err(|err| format!("Cannot find the input: {}", err))?;
+        // SAFETY: the function of the input takes no arguments and returns an `i32`, and the functions and globals it
+        // refers to are either mapped above or defined by the modules of earlier inputs. A panic unwinds out of it
+        // through `iranta_repl_panic`, which the `C-unwind` ABI allows.
+        let input_fn = unsafe { std::mem::transmute::<usize, unsafe extern "C-unwind" fn() -> i32>(address) };
+        let result = std::panic::catch_unwind(|| unsafe { input_fn() });
+        let _ = std::io::stdout().flush();
+        if let Err(payload) = result {
+            return match payload.downcast::<RuntimePanic>() {
+                Ok(panic) => Ok(Err(*panic)),
+                Err(payload) => std::panic::resume_unwind(payload),
+            };
+        }
+
+        // The value is read while the engine is alive, as a `str` points into the memory of the module.
+        let bytes = REPL_RESULT.with_borrow_mut(|result| result.take());
+        Ok(Ok(bytes.zip(self.repl_result_type).map(|(bytes, type_id)| Self::value_from_bytes(type_id, &bytes))))
+    }
+
+    /// Declare a top-level variable of an earlier REPL input, seen at the top level of the program.
+    pub fn declare_global(&mut self, global: &ReplGlobal) {
+        let value = self.module.add_global(self.get_basic_type(global.type_id), None, &global.link_name);
+        self.globals.insert(global.name, value.as_pointer_value());
+    }
+
+    /// Declare a function of an earlier REPL input.
+    pub fn declare_extern_function(&mut self, function: &ReplFunction) {
+        let fn_type = self.get_fn_type(&function.param_types, function.return_type);
+        let value = self.module.add_function(&function.link_name, fn_type, None);
+        self.functions.insert(function.name, value);
+    }
+
+    /// Give back the top-level variables and the functions defined by a REPL input.
+    pub fn take_repl_definitions(&mut self) -> (Vec<ReplGlobal>, Vec<ReplFunction>) {
+        (std::mem::take(&mut self.repl_globals), std::mem::take(&mut self.repl_functions))
+    }
+
+    #[must_use]
+    pub fn into_parts(self) -> (SymbolInterner, TypeInterner) {
+        (self.symbols, self.type_interner)
+    }
+
+    /// Add the global holding a top-level variable of a REPL input.
+    pub(super) fn add_repl_global(&mut self, name: Symbol, type_id: TypeId, mutable: bool) -> PointerValue<'ctx> {
+        let basic_type = self.get_basic_type(type_id);
+        // LLVM makes the name unique if the input binds the same name twice.
+        let global = self.module.add_global(basic_type, None, &format!("{}.{}", self.symbols.get_name(name), self.output_file));
+        global.set_linkage(Linkage::External);
+        global.set_initializer(&basic_type.const_zero());
+        self.repl_globals.push(ReplGlobal {
+            name,
+            type_id,
+            mutable,
+            link_name: global.get_name().to_string_lossy().into_owned(),
+        });
+        global.as_pointer_value()
+    }
+
+    fn jit_opt_level(arg_context: &ArgContext) -> OptimizationLevel {
+        match arg_context.opt_level() {
+            Some(OptLevel::O0) => OptimizationLevel::None,
+            Some(OptLevel::O1) => OptimizationLevel::Less,
+            None | Some(OptLevel::O2 | OptLevel::Os) => OptimizationLevel::Default,
+            Some(OptLevel::O3) => OptimizationLevel::Aggressive,
+        }
+    }
+
+    /// Map the functions of the standard library used by the module to the ones below.
+    fn map_runtime(&self, engine: &ExecutionEngine<'ctx>) {
+        let runtime: [(&str, usize); 17] = [
+            ("std_print_int8", runtime::std_print_int8 as *const () as usize),
+            ("std_print_uint8", runtime::std_print_uint8 as *const () as usize),
+            ("std_print_int16", runtime::std_print_int16 as *const () as usize),
+            ("std_print_uint16", runtime::std_print_uint16 as *const () as usize),
+            ("std_print_int32", runtime::std_print_int32 as *const () as usize),
+            ("std_print_uint32", runtime::std_print_uint32 as *const () as usize),
+            ("std_print_int64", runtime::std_print_int64 as *const () as usize),
+            ("std_print_uint64", runtime::std_print_uint64 as *const () as usize),
+            ("std_print_int128", runtime::std_print_int128 as *const () as usize),
+            ("std_print_uint128", runtime::std_print_uint128 as *const () as usize),
+            ("std_print_float32", runtime::std_print_float32 as *const () as usize),
+            ("std_print_float64", runtime::std_print_float64 as *const () as usize),
+            ("std_print_bool", runtime::std_print_bool as *const () as usize),
+            ("std_print_char", runtime::std_print_char as *const () as usize),
+            ("std_print_str", runtime::std_print_str as *const () as usize),
+            ("std_println", runtime::std_println as *const () as usize),
+            ("std_panic", if self.repl { iranta_repl_panic as *const () as usize } else { runtime::std_panic as *const () as usize }),
+        ];
+        for (name, address) in runtime {
+            if let Some(function) = self.module.get_function(name) {
+                engine.add_global_mapping(&function, address);
+            }
+        }
+    }
+
+    /// Rebuild a value of `type_id` from its bytes in memory.
+    fn value_from_bytes(type_id: TypeId, bytes: &[u8]) -> Value {
+        fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+            bytes[..N].try_into().unwrap()
+        }
+
+        match type_id {
+            TypeId::INT8 => Value::Integer(ValueInteger::Int8(i8::from_ne_bytes(array(bytes)))),
+            TypeId::UINT8 => Value::Integer(ValueInteger::UInt8(u8::from_ne_bytes(array(bytes)))),
+            TypeId::INT16 => Value::Integer(ValueInteger::Int16(i16::from_ne_bytes(array(bytes)))),
+            TypeId::UINT16 => Value::Integer(ValueInteger::UInt16(u16::from_ne_bytes(array(bytes)))),
+            TypeId::INT32 => Value::Integer(ValueInteger::Int32(i32::from_ne_bytes(array(bytes)))),
+            TypeId::UINT32 => Value::Integer(ValueInteger::UInt32(u32::from_ne_bytes(array(bytes)))),
+            TypeId::INT64 => Value::Integer(ValueInteger::Int64(i64::from_ne_bytes(array(bytes)))),
+            TypeId::UINT64 => Value::Integer(ValueInteger::UInt64(u64::from_ne_bytes(array(bytes)))),
+            TypeId::INT128 => Value::Integer(ValueInteger::Int128(i128::from_ne_bytes(array(bytes)))),
+            TypeId::UINT128 => Value::Integer(ValueInteger::UInt128(u128::from_ne_bytes(array(bytes)))),
+            TypeId::FLOAT32 => Value::Float(ValueFloat::Float32(f32::from_ne_bytes(array(bytes)))),
+            TypeId::FLOAT64 => Value::Float(ValueFloat::Float64(f64::from_ne_bytes(array(bytes)))),
+            TypeId::BOOL => Value::Bool(bytes[0] != 0),
+            TypeId::CHAR => Value::Char(char::from_u32(u32::from_ne_bytes(array(bytes))).unwrap_or(char::REPLACEMENT_CHARACTER)),
+            TypeId::STR => {
+                let ptr = usize::from_ne_bytes(array(bytes)) as *const u8;
+                let len = i64::from_ne_bytes(array(&bytes[size_of::<usize>()..])) as usize;
+                // SAFETY: a `str` value points to `len` bytes of a global constant of the module, which is still alive.
+                let string = unsafe { std::slice::from_raw_parts(ptr, len) };
+                Value::Str(String::from_utf8_lossy(string).into_owned())
+            }
+            _ => unreachable!("Only values that can be printed are passed to 'iranta_repl_result'."),
+        }
+    }
+}
+
+/// Stop running a REPL input at a failed check in place of `std_panic`, unwinding to `run_repl`.
+unsafe extern "C-unwind" fn iranta_repl_panic(msg: *const u8, msg_len: i64, _file: *const u8, _file_len: i64, line: i64, col: i64) -> ! {
+    let msg = unsafe { std::slice::from_raw_parts(msg, msg_len as usize) };
+    let (line, col) = (line as usize, col as usize);
+    let pos = Position { start_line: line, end_line: line, start_col: col, end_col: col };
+    std::panic::resume_unwind(Box::new(RuntimePanic { pos, msg: String::from_utf8_lossy(msg).into_owned() }))
+}
+
+/// Keep the value of the tail expression of a REPL input, passed as a pointer to its `size` bytes.
+unsafe extern "C" fn iranta_repl_result(ptr: *const u8, size: i64) {
+    let bytes = unsafe { std::slice::from_raw_parts(ptr, size as usize) }.to_vec();
+    REPL_RESULT.with_borrow_mut(|result| *result = Some(bytes));
+}
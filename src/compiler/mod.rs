@@ -1,47 +1,91 @@
 //! The module of `Compiler`
 
-mod error;
+pub mod jit;
+pub mod output;
 
 use crate::ast::binary_ope::BinaryOperator;
-use crate::ast::expr::{Expr, ExprBinary, ExprLiteral, ExprType, ExprUnary};
-use crate::compiler::error::{CompileError, CompileResultList};
+use crate::ast::unary_ope::UnaryOperator;
 use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
 use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
-use inkwell::types::AnyTypeEnum;
-use inkwell::values::{BasicValue, BasicValueEnum};
+use crate::mir::{Body, BodyKind, Constant, Local, Mir, Operand, Place, Rvalue, Statement, StatementKind, Storage, Terminator};
+use inkwell::types::{AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+use inkwell::basic_block::BasicBlock;
+use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue, StructValue};
+use inkwell::intrinsics::Intrinsic;
+use inkwell::module::Linkage;
+use inkwell::{FloatPredicate, IntPredicate};
 use maplit::hashmap;
 use std::collections::HashMap;
-use std::io::Write;
 use inkwell::attributes::{Attribute, AttributeLoc};
-use crate::args::{ArgContext, OptionArg};
+use crate::args::{ArgContext, OptLevel};
+use crate::compiler::jit::{ReplFunction, ReplGlobal};
 
+/// The code generator, which turns the MIR of a program already checked by the analyzer into LLVM IR.
 pub struct Compiler<'ctx> {
     llvm_ctx: &'ctx inkwell::context::Context,
-    expr: Box<Expr>,
     module: inkwell::module::Module<'ctx>,
     builder: inkwell::builder::Builder<'ctx>,
     type_list: HashMap<TypeId, AnyTypeEnum<'ctx>>,
     type_interner: TypeInterner,
+    symbols: SymbolInterner,
+    /// The globals holding the top-level variables of earlier REPL inputs.
+    globals: HashMap<Symbol, PointerValue<'ctx>>,
+    functions: HashMap<Symbol, FunctionValue<'ctx>>,
+    /// The slots of the locals of the body being compiled, `None` for the ones of type `void` or `never`.
+    locals: Vec<Option<PointerValue<'ctx>>>,
+    /// The LLVM blocks of the MIR blocks of the body being compiled.
+    blocks: Vec<BasicBlock<'ctx>>,
     output_file: String,
+    /// Whether every generated function is marked `optnone`, for `-O0`.
+    no_optimize: bool,
+    /// Whether the program is a REPL input, whose top-level variables are globals that later inputs link to.
+    repl: bool,
+    /// Whether integer `+`, `-` and `*` panic when they overflow, for `--overflow-checks`.
+    overflow_checks: bool,
+    /// The name of the source file reported by runtime panics.
+    source_name: String,
+    /// The type of the value handed to the REPL by the tail expression, if there is one.
+    repl_result_type: Option<TypeId>,
+    repl_globals: Vec<ReplGlobal>,
+    repl_functions: Vec<ReplFunction>,
 }
 
-type ExprResult<'ctx> = (TypeId, BasicValueEnum<'ctx>);
+/// The numeric families that decide which cast instruction is used.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NumericClass {
+    Signed,
+    Unsigned,
+    Float,
+}
 
 impl<'ctx> Compiler<'ctx> {
     #[must_use]
-    pub fn new(expr: Box<Expr>, output_file: String, ctx: &'ctx inkwell::context::Context) -> Self {
+    pub fn new(symbols: SymbolInterner, type_interner: TypeInterner, output_file: String, ctx: &'ctx inkwell::context::Context) -> Self {
         Self {
             llvm_ctx: ctx,
-            expr,
             module: ctx.create_module(&output_file),
             builder: ctx.create_builder(),
-            type_list: Self::init_type_list(&ctx),
-            type_interner: TypeInterner::create(),
+            type_list: Self::init_type_list(ctx),
+            type_interner,
+            symbols,
+            globals: HashMap::new(),
+            functions: HashMap::new(),
+            locals: vec![],
+            blocks: vec![],
             output_file,
+            no_optimize: false,
+            repl: false,
+            overflow_checks: false,
+            source_name: String::new(),
+            repl_result_type: None,
+            repl_globals: vec![],
+            repl_functions: vec![],
         }
     }
 
-    fn init_type_list(ctx: &inkwell::context::Context) -> HashMap<TypeId, AnyTypeEnum> {
+    fn init_type_list(ctx: &inkwell::context::Context) -> HashMap<TypeId, AnyTypeEnum<'_>> {
         hashmap! {
             TypeId::VOID => ctx.void_type().into(),
             TypeId::INT8 => ctx.i8_type().into(),
@@ -56,177 +100,1087 @@ impl<'ctx> Compiler<'ctx> {
             TypeId::UINT128 => ctx.i128_type().into(),
             TypeId::FLOAT32 => ctx.f32_type().into(),
             TypeId::FLOAT64 => ctx.f64_type().into(),
+            TypeId::NEVER => ctx.void_type().into(),
+            TypeId::BOOL => ctx.bool_type().into(),
+            TypeId::CHAR => ctx.i32_type().into(),
+            TypeId::STR => ctx.struct_type(&[ctx.ptr_type(Default::default()).into(), ctx.i64_type().into()], false).into(),
         }
     }
 
-    pub fn compile(&self, arg_context: &ArgContext) -> CompileResultList<()> {
-        let main_fn_type = self.llvm_ctx.i32_type().fn_type(&[], false);
-        let main_fn = self.module.add_function("main", main_fn_type, None);
-        let entry_block = self.llvm_ctx.append_basic_block(main_fn, "entry");
-        self.builder.position_at_end(entry_block);
+    /// Generate the code of a program from its MIR.
+    pub fn compile(&mut self, mir: &Mir, arg_context: &ArgContext) {
+        self.no_optimize = arg_context.opt_level() == Some(OptLevel::O0);
+        self.repl = arg_context.is_repl();
+        self.overflow_checks = arg_context.overflow_checks();
+        self.source_name = arg_context.file_name.clone().unwrap_or_else(|| "<repl>".to_string());
 
-        for option in &arg_context.option_args {
-            if let OptionArg::NoOptimize = option {
-                let optnone = self.llvm_ctx.create_enum_attribute(Attribute::get_named_enum_kind_id("optnone"), 0);
-                main_fn.add_attribute(AttributeLoc::Function, optnone);
+        for body in &mir.bodies {
+            if let BodyKind::Fn(name) = body.kind {
+                self.declare_function(name, body);
             }
         }
+        for body in &mir.bodies {
+            self.compile_body(body, arg_context);
+        }
+    }
 
-        let (type_id, val) = self.compile_expression(&self.expr)?;
-
+    /// Print a value followed by a new line with the functions from the standard library.
+    fn compile_print(&self, type_id: TypeId, val: BasicValueEnum<'ctx>) {
+        let print_fn_name = format!("std_print_{}", self.type_interner.to_data_type[&type_id]);
         match type_id {
             TypeId::VOID => unreachable!(),
             TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 | TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 => {
                 let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.type_list[&type_id].into_int_type().into()], false);
-                let print_fn = self.module.add_function(&format!("std_print_{}", self.type_interner.to_data_type[&type_id]), print_fn_type, None);
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
                 self.builder.build_call(print_fn, &[val.into()], "call").unwrap();
             }
             TypeId::FLOAT32 | TypeId::FLOAT64 => {
                 let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.type_list[&type_id].into_float_type().into()], false);
-                let print_fn = self.module.add_function(&format!("std_print_{}", self.type_interner.to_data_type[&type_id]), print_fn_type, None);
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
+                self.builder.build_call(print_fn, &[val.into()], "call").unwrap();
+            }
+            TypeId::BOOL => {
+                let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.llvm_ctx.i8_type().into()], false);
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
+                let val = self.builder.build_int_z_extend(val.into_int_value(), self.llvm_ctx.i8_type(), "zext").unwrap();
                 self.builder.build_call(print_fn, &[val.into()], "call").unwrap();
             }
             TypeId::INT128 | TypeId::UINT128 => {
                 let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.llvm_ctx.ptr_type(Default::default()).into()], false);
-                let print_fn = self.module.add_function(&format!("std_print_{}", self.type_interner.to_data_type[&type_id]), print_fn_type, None);
-                let num_ptr = self.builder.build_alloca(self.type_list[&type_id].into_int_type(), "alloca").unwrap();
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
+                let num_ptr = self.build_entry_alloca(self.type_list[&type_id].into_int_type().into(), "alloca");
                 num_ptr.as_instruction_value().unwrap().set_alignment(16).unwrap();
                 self.builder.build_store(num_ptr, val.into_int_value()).unwrap();
                 self.builder.build_call(print_fn, &[num_ptr.into()], "call").unwrap();
             }
+            TypeId::CHAR => {
+                let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.llvm_ctx.i32_type().into()], false);
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
+                self.builder.build_call(print_fn, &[val.into()], "call").unwrap();
+            }
+            TypeId::STR => {
+                // The pointer and the length are passed apart, so that the runtime does not depend on how structs are passed.
+                let print_fn_type = self.llvm_ctx.void_type().fn_type(&[self.llvm_ctx.ptr_type(Default::default()).into(), self.llvm_ctx.i64_type().into()], false);
+                let print_fn = self.get_extern_fn(&print_fn_name, print_fn_type);
+                let ptr = self.builder.build_extract_value(val.into_struct_value(), 0, "str_ptr").unwrap();
+                let len = self.builder.build_extract_value(val.into_struct_value(), 1, "str_len").unwrap();
+                self.builder.build_call(print_fn, &[ptr.into(), len.into()], "call").unwrap();
+            }
             _ => unreachable!(),
         }
 
         let println_fn_type = self.llvm_ctx.void_type().fn_type(&[], false);
-        let println_fn = self.module.add_function("std_println", println_fn_type, None);
+        let println_fn = self.get_extern_fn("std_println", println_fn_type);
         self.builder.build_call(println_fn, &[], "call").unwrap();
+    }
+
+    /// Hand a value over to the REPL through `iranta_repl_result`, which receives a pointer to it and its size.
+    fn compile_repl_result(&mut self, type_id: TypeId, val: BasicValueEnum<'ctx>) {
+        let basic_type = self.get_basic_type(type_id);
+        let ptr = self.build_entry_alloca(basic_type, "repl_result");
+        self.builder.build_store(ptr, val).unwrap();
+        let size = self.builder.build_int_z_extend_or_bit_cast(basic_type.size_of().unwrap(), self.llvm_ctx.i64_type(), "size").unwrap();
+
+        let result_fn_type = self.llvm_ctx.void_type().fn_type(&[self.llvm_ctx.ptr_type(Default::default()).into(), self.llvm_ctx.i64_type().into()], false);
+        let result_fn = self.get_extern_fn("iranta_repl_result", result_fn_type);
+        self.builder.build_call(result_fn, &[ptr.into(), size.into()], "call").unwrap();
+        self.repl_result_type = Some(type_id);
+    }
+
+    /// Add a function defined in this module, kept away from the optimizer under `-O0`.
+    fn add_function(&self, name: &str, fn_type: FunctionType<'ctx>, linkage: Option<Linkage>) -> FunctionValue<'ctx> {
+        let function = self.module.add_function(name, fn_type, linkage);
+        if self.no_optimize {
+            // LLVM requires `optnone` to come with `noinline`.
+            for attribute in ["optnone", "noinline"] {
+                let attribute = self.llvm_ctx.create_enum_attribute(Attribute::get_named_enum_kind_id(attribute), 0);
+                function.add_attribute(AttributeLoc::Function, attribute);
+            }
+        }
+        function
+    }
+
+    /// Get an external function, declaring it on first use.
+    fn get_extern_fn(&self, name: &str, fn_type: FunctionType<'ctx>) -> FunctionValue<'ctx> {
+        self.module.get_function(name).unwrap_or_else(|| self.module.add_function(name, fn_type, None))
+    }
+
+    /// Allocate a stack slot in the entry block of the current function, so that it is allocated only once.
+    fn build_entry_alloca(&self, basic_type: BasicTypeEnum<'ctx>, name: &str) -> PointerValue<'ctx> {
+        let current_fn = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let entry_block = current_fn.get_first_basic_block().unwrap();
+        let alloca_builder = self.llvm_ctx.create_builder();
+        match entry_block.get_first_instruction() {
+            Some(instruction) => alloca_builder.position_before(&instruction),
+            None => alloca_builder.position_at_end(entry_block),
+        }
+        alloca_builder.build_alloca(basic_type, name).unwrap()
+    }
+
+    fn get_basic_type(&self, type_id: TypeId) -> BasicTypeEnum<'ctx> {
+        match self.type_interner.ref_target(type_id) {
+            Some(_) => self.llvm_ctx.ptr_type(Default::default()).into(),
+            None => self.type_list[&type_id].try_into().unwrap(),
+        }
+    }
+
+    /// The placeholder value of expressions of type `void` or `never`, which never reaches the generated code.
+    fn void_value(&self) -> BasicValueEnum<'ctx> {
+        self.llvm_ctx.struct_type(&[], false).const_zero().into()
+    }
 
-        self.builder.build_return(Some(&self.llvm_ctx.i32_type().const_int(0, false))).unwrap();
-
-        let mut output_bytecode = true;
-        for option in &arg_context.option_args {
-            if let OptionArg::EmitLLVM = option {
-                let path = std::path::Path::new(&self.output_file);
-                let output = path.with_extension("ll");
-                let mut file = std::fs::File::create(output).unwrap_or_else(|err| {
-                    eprintln!("Cannot open or create output file: {}", err);
-                    std::process::exit(1);
-                });
-                file.write_all(self.module.print_to_string().to_bytes()).unwrap_or_else(|err| {
-                    eprintln!("Cannot write to output file: {}", err);
-                    std::process::exit(1);
-                });
-                output_bytecode = false;
-                break;
-            }
-        }
-
-        if output_bytecode {
-            let path = std::path::Path::new(&self.output_file);
-            let output = path.with_extension("bc");
-            self.module.print_to_file(output).unwrap_or_else(|err| {
-                eprintln!("Cannot write to output file: {}", err);
-                std::process::exit(1);
+    fn get_fn_type(&self, param_types: &[TypeId], return_type: TypeId) -> FunctionType<'ctx> {
+        let llvm_param_types: Vec<BasicMetadataTypeEnum> = param_types.iter().map(|param_type| self.get_basic_type(*param_type).into()).collect();
+        match return_type {
+            TypeId::VOID | TypeId::NEVER => self.llvm_ctx.void_type().fn_type(&llvm_param_types, false),
+            _ => self.get_basic_type(return_type).fn_type(&llvm_param_types, false),
+        }
+    }
+
+    fn declare_function(&mut self, name: Symbol, body: &Body) {
+        let param_types: Vec<TypeId> = body.locals[1..=body.arg_cnt].iter().map(|param| param.type_id).collect();
+        let return_type = body.return_type();
+        let fn_type = self.get_fn_type(&param_types, return_type);
+        if self.repl {
+            // The name is made unique to the input, so that it cannot clash with a function of an input that panicked.
+            let link_name = format!("{}.{}", self.symbols.get_name(name), self.output_file);
+            let value = self.add_function(&link_name, fn_type, None);
+            self.functions.insert(name, value);
+            self.repl_functions.push(ReplFunction {
+                name,
+                param_types,
+                return_type,
+                link_name,
             });
+        } else {
+            let value = self.add_function(self.symbols.get_name(name), fn_type, None);
+            self.functions.insert(name, value);
+        }
+    }
+
+    /// Compile a body, giving each local a slot and each MIR block an LLVM block.
+    fn compile_body(&mut self, body: &Body, arg_context: &ArgContext) {
+        let fn_value = match body.kind {
+            BodyKind::TopLevel => {
+                // A REPL input is run through a function named after its module, as every input is added to the same JIT.
+                let main_fn_name = if self.repl { self.output_file.clone() } else { "main".to_string() };
+                self.add_function(&main_fn_name, self.llvm_ctx.i32_type().fn_type(&[], false), None)
+            }
+            BodyKind::Fn(name) => self.functions[&name],
+        };
+        let entry_block = self.llvm_ctx.append_basic_block(fn_value, "entry");
+        self.builder.position_at_end(entry_block);
+
+        self.locals = body.locals.iter().enumerate().map(|(i, local)| match (local.type_id, local.storage) {
+            (TypeId::VOID | TypeId::NEVER, _) => None,
+            (type_id, Storage::Stack) => {
+                let name = local.name.map_or_else(|| format!("_{}", i), |name| self.symbols.get_name(name).to_string());
+                Some(self.build_entry_alloca(self.get_basic_type(type_id), &name))
+            }
+            (type_id, Storage::Global { mutable }) => Some(self.add_repl_global(local.name.unwrap(), type_id, mutable)),
+            (_, Storage::ExternGlobal) => Some(self.globals[&local.name.unwrap()]),
+        }).collect();
+        for i in 0..body.arg_cnt {
+            if let Some(ptr) = self.locals[i + 1] {
+                self.builder.build_store(ptr, fn_value.get_nth_param(i as u32).unwrap()).unwrap();
+            }
         }
 
-        Ok(())
+        self.blocks = (0..body.blocks.len()).map(|i| self.llvm_ctx.append_basic_block(fn_value, &format!("bb{}", i))).collect();
+        self.builder.build_unconditional_branch(self.blocks[0]).unwrap();
+
+        for (block, llvm_block) in body.blocks.iter().zip(self.blocks.clone()) {
+            self.builder.position_at_end(llvm_block);
+            for stmt in &block.stmts {
+                self.compile_statement(body, stmt);
+            }
+            self.compile_terminator(body, &block.terminator, arg_context);
+        }
     }
 
-    fn compile_expression(&self, expr: &Expr) -> CompileResultList<ExprResult> {
-        match &expr.expr_type {
-            ExprType::Literal(literal) => self.compile_expr_literal(literal),
-            ExprType::Unary(unary) => self.compile_expr_unary(expr, unary),
-            ExprType::Binary(binary) => self.compile_expr_binary(expr, binary),
+    fn compile_statement(&mut self, body: &Body, stmt: &Statement) {
+        match &stmt.kind {
+            StatementKind::Assign(place, rvalue) => {
+                let value = self.compile_rvalue(body, rvalue, &stmt.pos);
+                // A value of type `void` or `never` has nowhere to go, but computing it may have effects.
+                if !matches!(body.place_type(*place, &self.type_interner), TypeId::VOID | TypeId::NEVER) {
+                    let ptr = self.compile_place(place);
+                    self.builder.build_store(ptr, value).unwrap();
+                }
+            }
+            StatementKind::StorageDead(_) | StatementKind::PlaceMention(_) => (),
         }
     }
 
-    fn compile_expr_literal(&self, literal: &ExprLiteral) -> CompileResultList<ExprResult> {
-        match &literal.value {
-            Value::Integer(integer) => {
-                match integer {
-                    ValueInteger::Int8(int) => Ok((TypeId::INT8, self.type_list[&TypeId::INT8].into_int_type().const_int(*int as u64, true).into())),
-                    ValueInteger::UInt8(int) => Ok((TypeId::UINT8, self.type_list[&TypeId::UINT8].into_int_type().const_int(*int as u64, false).into())),
-                    ValueInteger::Int16(int) => Ok((TypeId::INT16, self.type_list[&TypeId::INT16].into_int_type().const_int(*int as u64, true).into())),
-                    ValueInteger::UInt16(int) => Ok((TypeId::UINT16, self.type_list[&TypeId::UINT16].into_int_type().const_int(*int as u64, false).into())),
-                    ValueInteger::Int32(int) => Ok((TypeId::INT32, self.type_list[&TypeId::INT32].into_int_type().const_int(*int as u64, true).into())),
-                    ValueInteger::UInt32(int) => Ok((TypeId::UINT32, self.type_list[&TypeId::UINT32].into_int_type().const_int(*int as u64, false).into())),
-                    ValueInteger::Int64(int) => Ok((TypeId::INT8, self.type_list[&TypeId::INT64].into_int_type().const_int(*int as u64, true).into())),
-                    ValueInteger::UInt64(int) => Ok((TypeId::UINT8, self.type_list[&TypeId::UINT64].into_int_type().const_int(*int, false).into())),
-                    ValueInteger::Int128(int) => {
-                        let bytes = int.to_ne_bytes();
-                        let u64s = [u64::from_ne_bytes(bytes[..8].try_into().unwrap()), u64::from_ne_bytes(bytes[8..].try_into().unwrap())];
-                        Ok((TypeId::INT128, self.type_list[&TypeId::INT128].into_int_type().const_int_arbitrary_precision(&u64s).into()))
+    fn compile_terminator(&mut self, body: &Body, terminator: &Terminator, arg_context: &ArgContext) {
+        match terminator {
+            Terminator::Goto(target) => {
+                self.builder.build_unconditional_branch(self.blocks[target.0]).unwrap();
+            }
+            Terminator::SwitchBool { cond, then_block, else_block, .. } => {
+                let cond = self.compile_operand(body, cond).into_int_value();
+                self.builder.build_conditional_branch(cond, self.blocks[then_block.0], self.blocks[else_block.0]).unwrap();
+            }
+            Terminator::Return => match body.kind {
+                BodyKind::TopLevel => {
+                    self.compile_top_level_result(body, arg_context);
+                    self.builder.build_return(Some(&self.llvm_ctx.i32_type().const_int(0, false))).unwrap();
+                }
+                BodyKind::Fn(_) => match body.return_type() {
+                    TypeId::VOID => {
+                        self.builder.build_return(None).unwrap();
+                    }
+                    TypeId::NEVER => {
+                        self.builder.build_unreachable().unwrap();
                     }
-                    ValueInteger::UInt128(int) => {
-                        let bytes = int.to_ne_bytes();
-                        let u64s = [u64::from_ne_bytes(bytes[..8].try_into().unwrap()), u64::from_ne_bytes(bytes[8..].try_into().unwrap())];
-                        Ok((TypeId::UINT128, self.type_list[&TypeId::UINT128].into_int_type().const_int_arbitrary_precision(&u64s).into()))
+                    _ => {
+                        let value = self.compile_operand(body, &Operand::Copy(Place::local(Local(0))));
+                        self.builder.build_return(Some(&value)).unwrap();
                     }
+                },
+            },
+            Terminator::Unreachable => {
+                self.builder.build_unreachable().unwrap();
+            }
+        }
+    }
+
+    /// Show the value of the tail expression of the top level kept in `_0`, printed or handed to the REPL.
+    fn compile_top_level_result(&mut self, body: &Body, arg_context: &ArgContext) {
+        let mut type_id = body.return_type();
+        if type_id == TypeId::VOID || type_id == TypeId::NEVER {
+            return;
+        }
+        let mut val = self.compile_operand(body, &Operand::Copy(Place::local(Local(0))));
+        // A reference is shown as the value it points to.
+        while let Some((target_ty, _)) = self.type_interner.ref_target(type_id) {
+            val = self.builder.build_load(self.get_basic_type(target_ty), val.into_pointer_value(), "deref").unwrap();
+            type_id = target_ty;
+        }
+        if arg_context.is_repl() {
+            self.compile_repl_result(type_id, val);
+        } else {
+            self.compile_print(type_id, val);
+        }
+    }
+
+    /// Get the address of a place, going through the reference held by its local if it is dereferenced.
+    fn compile_place(&self, place: &Place) -> PointerValue<'ctx> {
+        let ptr = self.locals[place.local.0].unwrap();
+        if place.deref {
+            self.builder.build_load(self.llvm_ctx.ptr_type(Default::default()), ptr, "deref").unwrap().into_pointer_value()
+        } else {
+            ptr
+        }
+    }
+
+    fn compile_operand(&self, body: &Body, operand: &Operand) -> BasicValueEnum<'ctx> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => match body.place_type(*place, &self.type_interner) {
+                TypeId::VOID | TypeId::NEVER => self.void_value(),
+                type_id => {
+                    let ptr = self.compile_place(place);
+                    self.builder.build_load(self.get_basic_type(type_id), ptr, "load").unwrap()
+                }
+            },
+            Operand::Constant(constant) => self.compile_constant(constant),
+        }
+    }
+
+    /// Compile an rvalue of the statement at `pos`, which is where the checks made while the program runs report.
+    fn compile_rvalue(&mut self, body: &Body, rvalue: &Rvalue, pos: &Position) -> BasicValueEnum<'ctx> {
+        match rvalue {
+            Rvalue::Use(operand) => self.compile_operand(body, operand),
+            Rvalue::UnaryOp(ope, operand) => {
+                let operand_ty = body.operand_type(operand, &self.type_interner);
+                let val = self.compile_operand(body, operand);
+                if matches!(ope, UnaryOperator::Minus) && self.overflow_checks && Self::numeric_class(operand_ty) == NumericClass::Signed {
+                    // `-x` is computed as `0 - x`, which only overflows for the minimum of the type.
+                    let val = val.into_int_value();
+                    let sub_fn = self.get_overflow_intrinsic("sub", operand_ty);
+                    self.build_overflow_checked_call(sub_fn, val.get_type().const_zero(), val, "neg", "attempt to negate with overflow", pos)
+                } else {
+                    self.compile_unary_operation(*ope, operand_ty, val)
+                }
+            }
+            Rvalue::BinaryOp(ope, lhs, rhs) => {
+                let operand_ty = body.operand_type(lhs, &self.type_interner);
+                let lhs_val = self.compile_operand(body, lhs);
+                let rhs_val = self.compile_operand(body, rhs);
+                match ope {
+                    BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Power
+                        if self.overflow_checks && Self::numeric_class(operand_ty) != NumericClass::Float => {
+                        self.compile_overflow_checked_operation(*ope, operand_ty, lhs_val.into_int_value(), rhs_val.into_int_value(), pos)
+                    }
+                    BinaryOperator::Divide | BinaryOperator::Remainder if Self::numeric_class(operand_ty) != NumericClass::Float => {
+                        self.compile_division_checks(*ope, operand_ty, lhs_val.into_int_value(), rhs_val.into_int_value(), pos);
+                        self.compile_binary_operation(*ope, operand_ty, lhs_val, rhs_val)
+                    }
+                    BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                        let rhs_val = self.compile_shift_amount(*ope, rhs_val.into_int_value(), pos);
+                        self.compile_binary_operation(*ope, operand_ty, lhs_val, rhs_val.into())
+                    }
+                    _ => self.compile_binary_operation(*ope, operand_ty, lhs_val, rhs_val),
                 }
             }
+            Rvalue::Cast(operand, dst_ty) => {
+                let val = self.compile_operand(body, operand);
+                self.compile_cast(val, body.operand_type(operand, &self.type_interner), *dst_ty)
+            }
+            Rvalue::Ref { place, .. } => self.compile_place(place).into(),
+            Rvalue::Call(name, args) => {
+                let fn_value = self.functions[name];
+                let args: Vec<BasicMetadataValueEnum> = args.iter().map(|arg| self.compile_operand(body, arg).into()).collect();
+                let call_site = self.builder.build_call(fn_value, &args, "call").unwrap();
+                call_site.try_as_basic_value().left().unwrap_or_else(|| self.void_value())
+            }
+        }
+    }
+
+    /// Classify a type for casting, where `bool` only appears as an unsigned source and `char` as an unsigned integer.
+    fn numeric_class(type_id: TypeId) -> NumericClass {
+        match type_id {
+            TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 => NumericClass::Signed,
+            TypeId::UINT8 | TypeId::UINT16 | TypeId::UINT32 | TypeId::UINT64 | TypeId::UINT128 | TypeId::BOOL | TypeId::CHAR => NumericClass::Unsigned,
+            TypeId::FLOAT32 | TypeId::FLOAT64 => NumericClass::Float,
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_cast(&self, src_val: BasicValueEnum<'ctx>, src_ty: TypeId, dst_ty: TypeId) -> BasicValueEnum<'ctx> {
+        if src_ty == dst_ty {
+            return src_val;
+        }
+
+        let src_class = Self::numeric_class(src_ty);
+        let dst_type = self.get_basic_type(dst_ty);
+        match (src_class, Self::numeric_class(dst_ty)) {
+            (NumericClass::Float, NumericClass::Float) => {
+                let (src_val, dst_type) = (src_val.into_float_value(), dst_type.into_float_type());
+                if src_val.get_type() == self.llvm_ctx.f32_type() {
+                    self.builder.build_float_ext(src_val, dst_type, "fpext").unwrap().into()
+                } else {
+                    self.builder.build_float_trunc(src_val, dst_type, "fptrunc").unwrap().into()
+                }
+            }
+            (NumericClass::Float, dst_class) => {
+                // Out-of-range values saturate and NaN gives zero, where `fptosi` and `fptoui` would give poison.
+                let name = if dst_class == NumericClass::Signed { "llvm.fptosi.sat" } else { "llvm.fptoui.sat" };
+                let intrinsic_fn = Intrinsic::find(name).unwrap().get_declaration(&self.module, &[dst_type, src_val.get_type()]).unwrap();
+                let result = self.builder.build_call(intrinsic_fn, &[src_val.into()], "fptoint").unwrap();
+                result.try_as_basic_value().left().unwrap()
+            }
+            (NumericClass::Signed, NumericClass::Float) => self.builder.build_signed_int_to_float(src_val.into_int_value(), dst_type.into_float_type(), "sitofp").unwrap().into(),
+            (NumericClass::Unsigned, NumericClass::Float) => self.builder.build_unsigned_int_to_float(src_val.into_int_value(), dst_type.into_float_type(), "uitofp").unwrap().into(),
+            (_, _) => {
+                let (src_val, dst_type) = (src_val.into_int_value(), dst_type.into_int_type());
+                let (src_width, dst_width) = (src_val.get_type().get_bit_width(), dst_type.get_bit_width());
+                if src_width > dst_width {
+                    self.builder.build_int_truncate(src_val, dst_type, "trunc").unwrap().into()
+                } else if src_width == dst_width {
+                    // Only the signedness changes, so the bits are kept as they are.
+                    src_val.into()
+                } else if src_class == NumericClass::Signed {
+                    self.builder.build_int_s_extend(src_val, dst_type, "sext").unwrap().into()
+                } else {
+                    self.builder.build_int_z_extend(src_val, dst_type, "zext").unwrap().into()
+                }
+            }
+        }
+    }
+
+    /// Build an integer constant of `type_id` from its bits, truncated to the width of the type.
+    fn const_int(&self, type_id: TypeId, bits: u128) -> IntValue<'ctx> {
+        let int_type = self.type_list[&type_id].into_int_type();
+        let bits = bits & (u128::MAX >> (128 - int_type.get_bit_width()));
+        int_type.const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64])
+    }
+
+    fn compile_constant(&self, constant: &Constant) -> BasicValueEnum<'ctx> {
+        match &constant.value {
+            Value::Integer(integer) => {
+                let bits = match integer {
+                    ValueInteger::Int8(int) => *int as u128,
+                    ValueInteger::UInt8(int) => *int as u128,
+                    ValueInteger::Int16(int) => *int as u128,
+                    ValueInteger::UInt16(int) => *int as u128,
+                    ValueInteger::Int32(int) => *int as u128,
+                    ValueInteger::UInt32(int) => *int as u128,
+                    ValueInteger::Int64(int) => *int as u128,
+                    ValueInteger::UInt64(int) => *int as u128,
+                    ValueInteger::Int128(int) => *int as u128,
+                    ValueInteger::UInt128(int) | ValueInteger::Untyped(int) => *int,
+                };
+                self.const_int(constant.type_id, bits).into()
+            }
             Value::Float(float) => {
-                match float {
-                    ValueFloat::Float32(float) => Ok((TypeId::FLOAT32, self.type_list[&TypeId::FLOAT32].into_float_type().const_float(*float as f64).into())),
-                    ValueFloat::Float64(float) => Ok((TypeId::FLOAT64, self.type_list[&TypeId::FLOAT64].into_float_type().const_float(*float).into())),
+                let float = match float {
+                    ValueFloat::Float32(float) => *float as f64,
+                    ValueFloat::Float64(float) | ValueFloat::Untyped(float) => *float,
+                };
+                self.type_list[&constant.type_id].into_float_type().const_float(float).into()
+            }
+            Value::Bool(boolean) => self.type_list[&TypeId::BOOL].into_int_type().const_int(*boolean as u64, false).into(),
+            Value::Char(ch) => self.type_list[&TypeId::CHAR].into_int_type().const_int(*ch as u64, false).into(),
+            Value::Str(string) => self.const_str(string).into(),
+        }
+    }
+
+    /// Build a `str` value whose bytes live in a private global constant.
+    fn const_str(&self, string: &str) -> StructValue<'ctx> {
+        let bytes = self.llvm_ctx.const_string(string.as_bytes(), false);
+        let global = self.module.add_global(bytes.get_type(), None, "str");
+        global.set_initializer(&bytes);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+        let len = self.llvm_ctx.i64_type().const_int(string.len() as u64, false);
+        self.type_list[&TypeId::STR].into_struct_type().const_named_struct(&[global.as_pointer_value().into(), len.into()])
+    }
+
+    fn compile_unary_operation(&self, ope: UnaryOperator, operand_ty: TypeId, val: BasicValueEnum<'ctx>) -> BasicValueEnum<'ctx> {
+        match ope {
+            UnaryOperator::Plus => val,
+            UnaryOperator::Minus => {
+                match operand_ty {
+                    TypeId::FLOAT32 | TypeId::FLOAT64 => self.builder.build_float_neg(val.into_float_value(), "neg").unwrap().into(),
+                    _ => self.builder.build_int_neg(val.into_int_value(), "neg").unwrap().into(),
                 }
             }
+            UnaryOperator::Not => self.builder.build_not(val.into_int_value(), "not").unwrap().into(),
+            UnaryOperator::BitNot => self.builder.build_not(val.into_int_value(), "bit_not").unwrap().into(),
         }
     }
 
-    fn compile_expr_unary(&self, expr: &Expr, unary: &ExprUnary) -> CompileResultList<ExprResult> {
-        let (rhs_ty, rhs_val) = self.compile_expression(&unary.rhs)?;
-        match rhs_ty {
-            TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 => Ok((rhs_ty, self.builder.build_int_neg(rhs_val.into_int_value(), "neg").unwrap().into())),
-            TypeId::FLOAT32 | TypeId::FLOAT64 => Ok((rhs_ty, self.builder.build_float_neg(rhs_val.into_float_value(), "neg").unwrap().into())),
-            _ => Err(CompileError::new(expr.pos.clone(), format!("Cannot use a negative sign on type '{}'.", self.type_interner.to_data_type[&rhs_ty])).into()),
+    fn int_predicate(ope: BinaryOperator, signed: bool) -> IntPredicate {
+        match (ope, signed) {
+            (BinaryOperator::Equal, _) => IntPredicate::EQ,
+            (BinaryOperator::NotEqual, _) => IntPredicate::NE,
+            (BinaryOperator::Less, true) => IntPredicate::SLT,
+            (BinaryOperator::Less, false) => IntPredicate::ULT,
+            (BinaryOperator::LessEqual, true) => IntPredicate::SLE,
+            (BinaryOperator::LessEqual, false) => IntPredicate::ULE,
+            (BinaryOperator::Greater, true) => IntPredicate::SGT,
+            (BinaryOperator::Greater, false) => IntPredicate::UGT,
+            (BinaryOperator::GreaterEqual, true) => IntPredicate::SGE,
+            (BinaryOperator::GreaterEqual, false) => IntPredicate::UGE,
+            _ => unreachable!(),
         }
     }
 
-    fn compile_expr_binary(&self, expr: &Expr, binary: &ExprBinary) -> CompileResultList<ExprResult> {
-        let (lhs_ty, lhs_val) = self.compile_expression(&binary.lhs)?;
-        let (rhs_ty, rhs_val) = self.compile_expression(&binary.rhs)?;
-        if lhs_ty != rhs_ty {
-            Err(CompileError::new(expr.pos.clone(), format!("Expected the same types, but found '{}' and '{}'.", self.type_interner.to_data_type[&lhs_ty], self.type_interner.to_data_type[&rhs_ty])).into())
-        } else {
-            match &binary.ope {
-                BinaryOperator::Plus => {
-                    match lhs_ty {
-                        TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
-                        TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 =>
-                            Ok((lhs_ty, self.builder.build_int_add(lhs_val.into_int_value(), rhs_val.into_int_value(), "add").unwrap().into())),
-                        TypeId::FLOAT32 | TypeId::FLOAT64 => Ok((lhs_ty, self.builder.build_float_add(lhs_val.into_float_value(), rhs_val.into_float_value(), "add").unwrap().into())),
-                        _ => unreachable!(),
-                    }
+    /// Comparisons involving NaN are false, except `!=` which is true so that `x != x` detects NaN.
+    fn float_predicate(ope: BinaryOperator) -> FloatPredicate {
+        match ope {
+            BinaryOperator::Equal => FloatPredicate::OEQ,
+            BinaryOperator::NotEqual => FloatPredicate::UNE,
+            BinaryOperator::Less => FloatPredicate::OLT,
+            BinaryOperator::LessEqual => FloatPredicate::OLE,
+            BinaryOperator::Greater => FloatPredicate::OGT,
+            BinaryOperator::GreaterEqual => FloatPredicate::OGE,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Compile a binary operation whose operands both have type `operand_ty`, as checked by the analyzer.
+    fn compile_binary_operation(&self, ope: BinaryOperator, operand_ty: TypeId, lhs_val: BasicValueEnum<'ctx>, rhs_val: BasicValueEnum<'ctx>) -> BasicValueEnum<'ctx> {
+        match ope {
+            BinaryOperator::Plus => {
+                match operand_ty {
+                    TypeId::FLOAT32 | TypeId::FLOAT64 => self.builder.build_float_add(lhs_val.into_float_value(), rhs_val.into_float_value(), "add").unwrap().into(),
+                    _ => self.builder.build_int_add(lhs_val.into_int_value(), rhs_val.into_int_value(), "add").unwrap().into(),
                 }
-                BinaryOperator::Minus => {
-                    match lhs_ty {
-                        TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
-                        TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 =>
-                            Ok((lhs_ty, self.builder.build_int_sub(lhs_val.into_int_value(), rhs_val.into_int_value(), "sub").unwrap().into())),
-                        TypeId::FLOAT32 | TypeId::FLOAT64 => Ok((lhs_ty, self.builder.build_float_sub(lhs_val.into_float_value(), rhs_val.into_float_value(), "sub").unwrap().into())),
-                        _ => unreachable!(),
-                    }
+            }
+            BinaryOperator::Minus => {
+                match operand_ty {
+                    TypeId::FLOAT32 | TypeId::FLOAT64 => self.builder.build_float_sub(lhs_val.into_float_value(), rhs_val.into_float_value(), "sub").unwrap().into(),
+                    _ => self.builder.build_int_sub(lhs_val.into_int_value(), rhs_val.into_int_value(), "sub").unwrap().into(),
                 }
-                BinaryOperator::Multiply => {
-                    match lhs_ty {
-                        TypeId::INT8 | TypeId::UINT8 | TypeId::INT16 | TypeId::UINT16 | TypeId::INT32 |
-                        TypeId::UINT32 | TypeId::INT64 | TypeId::UINT64 | TypeId::INT128 | TypeId::UINT128 =>
-                            Ok((lhs_ty, self.builder.build_int_mul(lhs_val.into_int_value(), rhs_val.into_int_value(), "mul").unwrap().into())),
-                        TypeId::FLOAT32 | TypeId::FLOAT64 => Ok((lhs_ty, self.builder.build_float_mul(lhs_val.into_float_value(), rhs_val.into_float_value(), "add").unwrap().into())),
-                        _ => unreachable!(),
-                    }
+            }
+            BinaryOperator::Multiply => {
+                match operand_ty {
+                    TypeId::FLOAT32 | TypeId::FLOAT64 => self.builder.build_float_mul(lhs_val.into_float_value(), rhs_val.into_float_value(), "mul").unwrap().into(),
+                    _ => self.builder.build_int_mul(lhs_val.into_int_value(), rhs_val.into_int_value(), "mul").unwrap().into(),
+                }
+            }
+            BinaryOperator::Divide => {
+                match operand_ty {
+                    TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 => self.builder.build_int_signed_div(lhs_val.into_int_value(), rhs_val.into_int_value(), "sdiv").unwrap().into(),
+                    TypeId::UINT8 | TypeId::UINT16 | TypeId::UINT32 | TypeId::UINT64 | TypeId::UINT128 => self.builder.build_int_unsigned_div(lhs_val.into_int_value(), rhs_val.into_int_value(), "udiv").unwrap().into(),
+                    _ => self.builder.build_float_div(lhs_val.into_float_value(), rhs_val.into_float_value(), "div").unwrap().into(),
+                }
+            }
+            BinaryOperator::Remainder => {
+                match operand_ty {
+                    TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 => self.builder.build_int_signed_rem(lhs_val.into_int_value(), rhs_val.into_int_value(), "srem").unwrap().into(),
+                    TypeId::UINT8 | TypeId::UINT16 | TypeId::UINT32 | TypeId::UINT64 | TypeId::UINT128 => self.builder.build_int_unsigned_rem(lhs_val.into_int_value(), rhs_val.into_int_value(), "urem").unwrap().into(),
+                    _ => self.builder.build_float_rem(lhs_val.into_float_value(), rhs_val.into_float_value(), "rem").unwrap().into(),
                 }
-                BinaryOperator::Divide => {
-                    match lhs_ty {
-                        TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 => Ok((lhs_ty, self.builder.build_int_signed_div(lhs_val.into_int_value(), rhs_val.into_int_value(), "sdiv").unwrap().into())),
-                        TypeId::UINT8 | TypeId::UINT16 | TypeId::UINT32 | TypeId::UINT64 | TypeId::UINT128 => Ok((lhs_ty, self.builder.build_int_unsigned_div(lhs_val.into_int_value(), rhs_val.into_int_value(), "udiv").unwrap().into())),
-                        TypeId::FLOAT32 | TypeId::FLOAT64 => Ok((lhs_ty, self.builder.build_float_div(lhs_val.into_float_value(), rhs_val.into_float_value(), "div").unwrap().into())),
-                        _ => unreachable!(),
+            }
+            BinaryOperator::Power => {
+                match operand_ty {
+                    TypeId::FLOAT32 | TypeId::FLOAT64 => self.build_float_pow(lhs_val.into_float_value(), rhs_val.into_float_value()),
+                    _ => {
+                        let pow_fn = self.get_int_pow_fn(operand_ty, false);
+                        let val = self.builder.build_call(pow_fn, &[lhs_val.into(), rhs_val.into()], "pow").unwrap();
+                        val.try_as_basic_value().left().unwrap()
                     }
                 }
-                BinaryOperator::Power => unimplemented!("Waiting for function support."),
             }
+
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::Less |
+            BinaryOperator::LessEqual | BinaryOperator::Greater | BinaryOperator::GreaterEqual => {
+                match operand_ty {
+                    TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128 =>
+                        self.builder.build_int_compare(Self::int_predicate(ope, true), lhs_val.into_int_value(), rhs_val.into_int_value(), "icmp").unwrap().into(),
+                    TypeId::FLOAT32 | TypeId::FLOAT64 =>
+                        self.builder.build_float_compare(Self::float_predicate(ope), lhs_val.into_float_value(), rhs_val.into_float_value(), "fcmp").unwrap().into(),
+                    _ => self.builder.build_int_compare(Self::int_predicate(ope, false), lhs_val.into_int_value(), rhs_val.into_int_value(), "icmp").unwrap().into(),
+                }
+            }
+            BinaryOperator::BitAnd => self.builder.build_and(lhs_val.into_int_value(), rhs_val.into_int_value(), "and").unwrap().into(),
+            BinaryOperator::BitOr => self.builder.build_or(lhs_val.into_int_value(), rhs_val.into_int_value(), "or").unwrap().into(),
+            BinaryOperator::BitXor => self.builder.build_xor(lhs_val.into_int_value(), rhs_val.into_int_value(), "xor").unwrap().into(),
+            BinaryOperator::ShiftLeft => self.builder.build_left_shift(lhs_val.into_int_value(), rhs_val.into_int_value(), "shl").unwrap().into(),
+            BinaryOperator::ShiftRight => {
+                let signed = matches!(operand_ty, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128);
+                self.builder.build_right_shift(lhs_val.into_int_value(), rhs_val.into_int_value(), signed, if signed { "ashr" } else { "lshr" }).unwrap().into()
+            }
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("Logical operators are lowered to branches in the MIR."),
         }
     }
+
+    /// Raise a float to a power, with `llvm.powi` if the exponent is a constant integer and `llvm.pow` otherwise.
+    fn build_float_pow(&self, base: FloatValue<'ctx>, exp: FloatValue<'ctx>) -> BasicValueEnum<'ctx> {
+        let float_type = base.get_type();
+        let int_exp = exp.get_constant().map(|(exp, _)| exp).filter(|exp| exp.fract() == 0.0 && exp.abs() <= i32::MAX as f64);
+        let val = match int_exp {
+            Some(int_exp) => {
+                let i32_type = self.llvm_ctx.i32_type();
+                let powi_fn = Intrinsic::find("llvm.powi").unwrap().get_declaration(&self.module, &[float_type.into(), i32_type.into()]).unwrap();
+                self.builder.build_call(powi_fn, &[base.into(), i32_type.const_int(int_exp as i64 as u64, true).into()], "powi")
+            }
+            None => {
+                let pow_fn = Intrinsic::find("llvm.pow").unwrap().get_declaration(&self.module, &[float_type.into()]).unwrap();
+                self.builder.build_call(pow_fn, &[base.into(), exp.into()], "pow")
+            }
+        };
+        val.unwrap().try_as_basic_value().left().unwrap()
+    }
+
+    /// Compile an integer `+`, `-`, `*` or `**` that panics if it overflows.
+    fn compile_overflow_checked_operation(&self, ope: BinaryOperator, operand_ty: TypeId, lhs_val: IntValue<'ctx>, rhs_val: IntValue<'ctx>, pos: &Position) -> BasicValueEnum<'ctx> {
+        let (name, msg) = match ope {
+            BinaryOperator::Plus => ("add", "attempt to add with overflow"),
+            BinaryOperator::Minus => ("sub", "attempt to subtract with overflow"),
+            BinaryOperator::Multiply => ("mul", "attempt to multiply with overflow"),
+            BinaryOperator::Power => ("pow", "attempt to multiply with overflow"),
+            _ => unreachable!(),
+        };
+        let checked_fn = match ope {
+            BinaryOperator::Power => self.get_int_pow_fn(operand_ty, true),
+            _ => self.get_overflow_intrinsic(name, operand_ty),
+        };
+        self.build_overflow_checked_call(checked_fn, lhs_val, rhs_val, name, msg, pos)
+    }
+
+    /// Get the `llvm.{s,u}{name}.with.overflow` intrinsic of an integer type, `name` being `add`, `sub` or `mul`.
+    fn get_overflow_intrinsic(&self, name: &str, operand_ty: TypeId) -> FunctionValue<'ctx> {
+        let sign = if Self::numeric_class(operand_ty) == NumericClass::Signed { "s" } else { "u" };
+        let intrinsic = Intrinsic::find(&format!("llvm.{}{}.with.overflow", sign, name)).unwrap();
+        intrinsic.get_declaration(&self.module, &[self.get_basic_type(operand_ty)]).unwrap()
+    }
+
+    /// Call a function giving a result and whether it overflowed, and panic with `msg` if it did.
+    fn build_overflow_checked_call(&self, checked_fn: FunctionValue<'ctx>, lhs_val: IntValue<'ctx>, rhs_val: IntValue<'ctx>, name: &str, msg: &str, pos: &Position) -> BasicValueEnum<'ctx> {
+        let result = self.builder.build_call(checked_fn, &[lhs_val.into(), rhs_val.into()], name).unwrap();
+        let result = result.try_as_basic_value().left().unwrap().into_struct_value();
+        let overflow = self.builder.build_extract_value(result, 1, "overflow").unwrap().into_int_value();
+        self.build_panic_if(overflow, msg, pos);
+        self.builder.build_extract_value(result, 0, name).unwrap()
+    }
+
+    /// Panic before an integer `/` or `%` by zero or of the signed minimum by `-1`, whatever the overflow checks.
+    fn compile_division_checks(&self, ope: BinaryOperator, operand_ty: TypeId, lhs_val: IntValue<'ctx>, rhs_val: IntValue<'ctx>, pos: &Position) {
+        let int_type = rhs_val.get_type();
+        let (zero_msg, overflow_msg) = match ope {
+            BinaryOperator::Divide => ("attempt to divide by zero", "attempt to divide with overflow"),
+            BinaryOperator::Remainder => ("attempt to calculate the remainder with a divisor of zero", "attempt to calculate the remainder with overflow"),
+            _ => unreachable!(),
+        };
+        let is_zero = self.builder.build_int_compare(IntPredicate::EQ, rhs_val, int_type.const_zero(), "is_zero").unwrap();
+        self.build_panic_if(is_zero, zero_msg, pos);
+
+        if Self::numeric_class(operand_ty) == NumericClass::Signed {
+            let bit_width = int_type.get_bit_width();
+            let min = if bit_width == 128 {
+                int_type.const_int_arbitrary_precision(&[0, 1 << 63])
+            } else {
+                int_type.const_int(1 << (bit_width - 1), false)
+            };
+            let is_min = self.builder.build_int_compare(IntPredicate::EQ, lhs_val, min, "is_min").unwrap();
+            let is_minus_one = self.builder.build_int_compare(IntPredicate::EQ, rhs_val, int_type.const_all_ones(), "is_minus_one").unwrap();
+            let overflow = self.builder.build_and(is_min, is_minus_one, "overflow").unwrap();
+            self.build_panic_if(overflow, overflow_msg, pos);
+        }
+    }
+
+    /// Panic on a shift amount not below the width of the type when overflow checks are on, and mask it otherwise.
+    fn compile_shift_amount(&self, ope: BinaryOperator, amount: IntValue<'ctx>, pos: &Position) -> IntValue<'ctx> {
+        let int_type = amount.get_type();
+        let bit_width = int_type.get_bit_width() as u64;
+        if self.overflow_checks {
+            // A negative amount is a huge unsigned one, so a single unsigned comparison also catches it.
+            let too_large = self.builder.build_int_compare(IntPredicate::UGE, amount, int_type.const_int(bit_width, false), "too_large").unwrap();
+            let msg = match ope {
+                BinaryOperator::ShiftLeft => "attempt to shift left with overflow",
+                _ => "attempt to shift right with overflow",
+            };
+            self.build_panic_if(too_large, msg, pos);
+            amount
+        } else {
+            self.builder.build_and(amount, int_type.const_int(bit_width - 1, false), "mask").unwrap()
+        }
+    }
+
+    /// Call the panic function of the runtime with `msg` and `pos` when `cond` holds, and go on in a new block otherwise.
+    fn build_panic_if(&self, cond: IntValue<'ctx>, msg: &str, pos: &Position) {
+        let current_fn = self.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let panic_block = self.llvm_ctx.append_basic_block(current_fn, "panic");
+        let continue_block = self.llvm_ctx.append_basic_block(current_fn, "continue");
+        self.builder.build_conditional_branch(cond, panic_block, continue_block).unwrap();
+
+        self.builder.position_at_end(panic_block);
+        let ptr_type = self.llvm_ctx.ptr_type(Default::default());
+        let i64_type = self.llvm_ctx.i64_type();
+        let panic_fn = self.module.get_function("std_panic").unwrap_or_else(|| {
+            let panic_fn_type = self.llvm_ctx.void_type().fn_type(&[ptr_type.into(), i64_type.into(), ptr_type.into(), i64_type.into(), i64_type.into(), i64_type.into()], false);
+            let panic_fn = self.module.add_function("std_panic", panic_fn_type, None);
+            for attribute in ["noreturn", "cold"] {
+                let attribute = self.llvm_ctx.create_enum_attribute(Attribute::get_named_enum_kind_id(attribute), 0);
+                panic_fn.add_attribute(AttributeLoc::Function, attribute);
+            }
+            panic_fn
+        });
+        let msg = self.const_str(msg);
+        let file = self.const_str(&self.source_name);
+        let args = [
+            self.builder.build_extract_value(msg, 0, "msg_ptr").unwrap().into(),
+            self.builder.build_extract_value(msg, 1, "msg_len").unwrap().into(),
+            self.builder.build_extract_value(file, 0, "file_ptr").unwrap().into(),
+            self.builder.build_extract_value(file, 1, "file_len").unwrap().into(),
+            i64_type.const_int(pos.start_line as u64, false).into(),
+            i64_type.const_int(pos.start_col as u64, false).into(),
+        ];
+        self.builder.build_call(panic_fn, &args, "call").unwrap();
+        self.builder.build_unreachable().unwrap();
+
+        self.builder.position_at_end(continue_block);
+    }
+
+    /// Get the helper computing `base ** exp` for an integer type, also giving whether it overflowed if `checked`.
+    fn get_int_pow_fn(&self, type_id: TypeId, checked: bool) -> FunctionValue<'ctx> {
+        let fn_name = format!("iranta_pow{}_{}", if checked { "_checked" } else { "" }, self.type_interner.to_data_type[&type_id]);
+        if let Some(pow_fn) = self.module.get_function(&fn_name) {
+            return pow_fn;
+        }
+
+        let signed = matches!(type_id, TypeId::INT8 | TypeId::INT16 | TypeId::INT32 | TypeId::INT64 | TypeId::INT128);
+        let int_type = self.type_list[&type_id].into_int_type();
+        let bool_type = self.llvm_ctx.bool_type();
+        let fn_type = if checked {
+            self.llvm_ctx.struct_type(&[int_type.into(), bool_type.into()], false).fn_type(&[int_type.into(), int_type.into()], false)
+        } else {
+            int_type.fn_type(&[int_type.into(), int_type.into()], false)
+        };
+        let pow_fn = self.add_function(&fn_name, fn_type, Some(Linkage::Private));
+        let saved_block = self.builder.get_insert_block();
+
+        let entry_block = self.llvm_ctx.append_basic_block(pow_fn, "entry");
+        let loop_block = self.llvm_ctx.append_basic_block(pow_fn, "loop");
+        let body_block = self.llvm_ctx.append_basic_block(pow_fn, "body");
+        let exit_block = self.llvm_ctx.append_basic_block(pow_fn, "exit");
+
+        let base = pow_fn.get_nth_param(0).unwrap().into_int_value();
+        let exp = pow_fn.get_nth_param(1).unwrap().into_int_value();
+        let zero = int_type.const_zero();
+        let one = int_type.const_int(1, false);
+        let no_overflow = bool_type.const_zero();
+
+        let build_return = |result: IntValue<'ctx>, overflow: IntValue<'ctx>| {
+            if checked {
+                let ret_type = pow_fn.get_type().get_return_type().unwrap().into_struct_type();
+                let ret = self.builder.build_insert_value(ret_type.get_undef(), result, 0, "ret").unwrap();
+                let ret = self.builder.build_insert_value(ret, overflow, 1, "ret").unwrap();
+                self.builder.build_return(Some(&ret)).unwrap();
+            } else {
+                self.builder.build_return(Some(&result)).unwrap();
+            }
+        };
+        // Multiply, giving whether the product overflowed when checked.
+        let build_mul = |lhs: IntValue<'ctx>, rhs: IntValue<'ctx>, name: &str| {
+            if checked {
+                let mul_fn = self.get_overflow_intrinsic("mul", type_id);
+                let result = self.builder.build_call(mul_fn, &[lhs.into(), rhs.into()], name).unwrap();
+                let result = result.try_as_basic_value().left().unwrap().into_struct_value();
+                let product = self.builder.build_extract_value(result, 0, name).unwrap().into_int_value();
+                let overflow = self.builder.build_extract_value(result, 1, "overflow").unwrap().into_int_value();
+                (product, overflow)
+            } else {
+                (self.builder.build_int_mul(lhs, rhs, name).unwrap(), no_overflow)
+            }
+        };
+
+        self.builder.position_at_end(entry_block);
+        if signed {
+            let negative_block = self.llvm_ctx.append_basic_block(pow_fn, "negative");
+            let is_negative = self.builder.build_int_compare(IntPredicate::SLT, exp, zero, "is_negative").unwrap();
+            self.builder.build_conditional_branch(is_negative, negative_block, loop_block).unwrap();
+
+            self.builder.position_at_end(negative_block);
+            let minus_one = int_type.const_all_ones();
+            let exp_odd = self.builder.build_and(exp, one, "exp_odd").unwrap();
+            let exp_odd = self.builder.build_int_compare(IntPredicate::NE, exp_odd, zero, "exp_odd").unwrap();
+            let minus_one_result = self.builder.build_select(exp_odd, minus_one, one, "minus_one_result").unwrap().into_int_value();
+            let base_is_one = self.builder.build_int_compare(IntPredicate::EQ, base, one, "base_is_one").unwrap();
+            let base_is_minus_one = self.builder.build_int_compare(IntPredicate::EQ, base, minus_one, "base_is_minus_one").unwrap();
+            let result = self.builder.build_select(base_is_minus_one, minus_one_result, zero, "result").unwrap().into_int_value();
+            let result = self.builder.build_select(base_is_one, one, result, "result").unwrap().into_int_value();
+            build_return(result, no_overflow);
+        } else {
+            self.builder.build_unconditional_branch(loop_block).unwrap();
+        }
+
+        self.builder.position_at_end(loop_block);
+        let acc = self.builder.build_phi(int_type, "acc").unwrap();
+        let cur_base = self.builder.build_phi(int_type, "cur_base").unwrap();
+        let cur_exp = self.builder.build_phi(int_type, "cur_exp").unwrap();
+        let overflow = self.builder.build_phi(bool_type, "overflow").unwrap();
+        let acc_val = acc.as_basic_value().into_int_value();
+        let cur_base_val = cur_base.as_basic_value().into_int_value();
+        let cur_exp_val = cur_exp.as_basic_value().into_int_value();
+        let overflow_val = overflow.as_basic_value().into_int_value();
+        let finished = self.builder.build_int_compare(IntPredicate::EQ, cur_exp_val, zero, "finished").unwrap();
+        self.builder.build_conditional_branch(finished, exit_block, body_block).unwrap();
+
+        self.builder.position_at_end(body_block);
+        let exp_odd = self.builder.build_and(cur_exp_val, one, "exp_odd").unwrap();
+        let exp_odd = self.builder.build_int_compare(IntPredicate::NE, exp_odd, zero, "exp_odd").unwrap();
+        let (multiplied, mul_overflow) = build_mul(acc_val, cur_base_val, "mul");
+        let next_acc = self.builder.build_select(exp_odd, multiplied, acc_val, "next_acc").unwrap().into_int_value();
+        let (next_base, square_overflow) = build_mul(cur_base_val, cur_base_val, "next_base");
+        let next_exp = self.builder.build_right_shift(cur_exp_val, one, false, "next_exp").unwrap();
+        // A product only counts when it is kept, and the base squared after the last bit of the exponent is not used.
+        let mul_overflow = self.builder.build_and(exp_odd, mul_overflow, "mul_overflow").unwrap();
+        let base_used = self.builder.build_int_compare(IntPredicate::NE, next_exp, zero, "base_used").unwrap();
+        let square_overflow = self.builder.build_and(base_used, square_overflow, "square_overflow").unwrap();
+        let next_overflow = self.builder.build_or(overflow_val, mul_overflow, "next_overflow").unwrap();
+        let next_overflow = self.builder.build_or(next_overflow, square_overflow, "next_overflow").unwrap();
+        self.builder.build_unconditional_branch(loop_block).unwrap();
+
+        acc.add_incoming(&[(&one, entry_block), (&next_acc, body_block)]);
+        cur_base.add_incoming(&[(&base, entry_block), (&next_base, body_block)]);
+        cur_exp.add_incoming(&[(&exp, entry_block), (&next_exp, body_block)]);
+        overflow.add_incoming(&[(&no_overflow, entry_block), (&next_overflow, body_block)]);
+
+        self.builder.position_at_end(exit_block);
+        build_return(acc_val, overflow_val);
+
+        if let Some(block) = saved_block {
+            self.builder.position_at_end(block);
+        }
+
+        pow_fn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::tests::analyze;
+    use crate::args::OptionArg;
+    use crate::mir::lower::MirBuilder;
+    use inkwell::OptimizationLevel;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static OUTPUT: RefCell<String> = const { RefCell::new(String::new()) };
+    }
+
+    macro_rules! capture_fn {
+        ($name:ident, $ty:ty) => {
+            extern "C" fn $name(value: $ty) {
+                OUTPUT.with_borrow_mut(|output| output.push_str(&value.to_string()));
+            }
+        };
+    }
+
+    capture_fn!(print_int8, i8);
+    capture_fn!(print_uint8, u8);
+    capture_fn!(print_int16, i16);
+    capture_fn!(print_uint16, u16);
+    capture_fn!(print_int32, i32);
+    capture_fn!(print_uint32, u32);
+    capture_fn!(print_int64, i64);
+    capture_fn!(print_uint64, u64);
+    capture_fn!(print_float32, f32);
+    capture_fn!(print_float64, f64);
+
+    extern "C" fn print_int128(value: *const i128) {
+        OUTPUT.with_borrow_mut(|output| output.push_str(&unsafe { *value }.to_string()));
+    }
+
+    extern "C" fn print_uint128(value: *const u128) {
+        OUTPUT.with_borrow_mut(|output| output.push_str(&unsafe { *value }.to_string()));
+    }
+
+    extern "C" fn print_bool(value: u8) {
+        OUTPUT.with_borrow_mut(|output| output.push_str(if value != 0 { "true" } else { "false" }));
+    }
+
+    extern "C" fn println() {
+        OUTPUT.with_borrow_mut(|output| output.push('\n'));
+    }
+
+    /// Unwind out of the program with the message of a failed check, which `run_with` adds to the output.
+    unsafe extern "C-unwind" fn panic(msg: *const u8, msg_len: i64, _file: *const u8, _file_len: i64, line: i64, col: i64) -> ! {
+        let msg = String::from_utf8_lossy(unsafe { std::slice::from_raw_parts(msg, msg_len as usize) }).into_owned();
+        std::panic::resume_unwind(Box::new(format!("panicked at {}:{}: {}", line, col, msg)))
+    }
+
+    /// Analyze, lower and compile `source`, keeping the module in memory only.
+    fn compile<'ctx>(source: &str, option_args: Vec<OptionArg>, ctx: &'ctx inkwell::context::Context) -> Compiler<'ctx> {
+        let (program, symbols, type_interner) = analyze(source).unwrap_or_else(|errors| panic!("compile error in {:?}: {}", source, errors[0].msg));
+        let mir = MirBuilder::new(&type_interner).build(&program);
+        let arg_context = ArgContext { option_args, file_name: None };
+        let mut compiler = Compiler::new(symbols, type_interner, "iranta_test".to_string(), ctx);
+        compiler.compile(&mir, &arg_context);
+        compiler
+    }
+
+    /// Compile `source` with the default options and run it with the JIT, returning what it prints.
+    fn run(source: &str) -> String {
+        run_with(source, vec![])
+    }
+
+    /// Compile `source` with `option_args` and run it with the JIT, returning what it prints and the panic ending it.
+    fn run_with(source: &str, option_args: Vec<OptionArg>) -> String {
+        let ctx = inkwell::context::Context::create();
+        let compiler = compile(source, option_args, &ctx);
+
+        let engine = compiler.module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+        let captures: [(&str, *const ()); 15] = [
+            ("std_print_int8", print_int8 as *const ()),
+            ("std_print_uint8", print_uint8 as *const ()),
+            ("std_print_int16", print_int16 as *const ()),
+            ("std_print_uint16", print_uint16 as *const ()),
+            ("std_print_int32", print_int32 as *const ()),
+            ("std_print_uint32", print_uint32 as *const ()),
+            ("std_print_int64", print_int64 as *const ()),
+            ("std_print_uint64", print_uint64 as *const ()),
+            ("std_print_int128", print_int128 as *const ()),
+            ("std_print_uint128", print_uint128 as *const ()),
+            ("std_print_float32", print_float32 as *const ()),
+            ("std_print_float64", print_float64 as *const ()),
+            ("std_print_bool", print_bool as *const ()),
+            ("std_println", println as *const ()),
+            ("std_panic", panic as *const ()),
+        ];
+        for (name, address) in captures {
+            if let Some(function) = compiler.module.get_function(name) {
+                engine.add_global_mapping(&function, address as usize);
+            }
+        }
+
+        OUTPUT.with_borrow_mut(|output| output.clear());
+        let address = engine.get_function_address("main").unwrap();
+        let main_fn = unsafe { std::mem::transmute::<usize, unsafe extern "C-unwind" fn() -> i32>(address) };
+        if let Err(payload) = std::panic::catch_unwind(|| unsafe { main_fn() }) {
+            let msg = payload.downcast::<String>().unwrap();
+            OUTPUT.with_borrow_mut(|output| output.push_str(&msg));
+        }
+        OUTPUT.with_borrow_mut(std::mem::take)
+    }
+
+    #[test]
+    fn int_power() {
+        assert_eq!(run("3 ** 4"), "81\n");
+        assert_eq!(run("2int64 ** 40int64"), "1099511627776\n");
+        assert_eq!(run("2uint128 ** 100uint128"), "1267650600228229401496703205376\n");
+        assert_eq!(run("7 ** 0"), "1\n");
+    }
+
+    #[test]
+    fn int_power_negative_exponent() {
+        assert_eq!(run("2 ** -1"), "0\n");
+        assert_eq!(run("1 ** -5"), "1\n");
+        assert_eq!(run("-1 ** -3"), "-1\n");
+    }
+
+    #[test]
+    fn float_power() {
+        assert_eq!(run("2.0 ** 10.0"), "1024\n");
+        assert_eq!(run("2.0 ** -2.0"), "0.25\n");
+        assert_eq!(run("4.0float32 ** 0.5float32"), "2\n");
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(run("2 ** 3 ** 2"), "512\n");
+    }
+
+    #[test]
+    fn let_and_assign() {
+        assert_eq!(run("let mut x = 1; x = x + 2; x *= 5; x"), "15\n");
+        assert_eq!(run("let x: float64 = 1.5; let y = x ** 2.0; y"), "2.25\n");
+        assert_eq!(run("let x = 1; let x = x + 1; x"), "2\n");
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(run("fn add(a: int32, b: int32) -> int32 { return a + b; } add(2, 3)"), "5\n");
+        assert_eq!(run("fn square(x: float64) -> float64 { x * x } square(1.5)"), "2.25\n");
+        assert_eq!(run("fn double(n: int64) -> int64 { n * 2int64 } fn quad(n: int64) -> int64 { double(double(n)) } quad(7int64)"), "28\n");
+    }
+
+    #[test]
+    fn if_else() {
+        assert_eq!(run("let x = 2; if x == 1 { 10 } else if x == 2 { 20 } else { 30 }"), "20\n");
+        assert_eq!(run("let mut x = 0; if true { x = 5; } x"), "5\n");
+    }
+
+    #[test]
+    fn while_and_continue() {
+        assert_eq!(run("let mut i = 0; let mut s = 0; while i != 5 { i += 1; if i == 3 { continue; } s += i; } s"), "12\n");
+    }
+
+    #[test]
+    fn break_with_value() {
+        assert_eq!(run("let x = loop { break 7; }; x"), "7\n");
+        assert_eq!(run("let x = 'a: loop { loop { break 'a 5; } }; x"), "5\n");
+    }
+
+    #[test]
+    fn labeled_continue_targets_outer_loop() {
+        assert_eq!(run("let mut n = 0; 'outer: loop { loop { n += 1; if n < 3 { continue 'outer; } break 'outer; } } n"), "3\n");
+        assert_eq!(run("let mut n = 0; 'outer: while n < 10 { n += 1; loop { break 'outer; } } n"), "1\n");
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(run("1 < 2"), "true\n");
+        assert_eq!(run("2.5 >= 3.0"), "false\n");
+        assert_eq!(run("true == !false"), "true\n");
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        assert_eq!(run("let mut n = 0; let b = false && { n += 1; true }; n"), "0\n");
+        assert_eq!(run("let mut n = 0; let b = true || { n += 1; false }; n"), "0\n");
+        assert_eq!(run("let mut n = 0; let b = true && { n += 1; true }; n"), "1\n");
+        assert_eq!(run("let mut n = 0; let b = false || { n += 1; true }; n"), "1\n");
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        assert_eq!(run("0b1100 & 0b1010"), "8\n");
+        assert_eq!(run("0b1100 | 0b1010"), "14\n");
+        assert_eq!(run("0b1100 ^ 0b1010"), "6\n");
+        assert_eq!(run("~0uint8"), "255\n");
+        assert_eq!(run("true ^ true"), "false\n");
+    }
+
+    #[test]
+    fn right_shift_follows_signedness() {
+        assert_eq!(run("-8 >> 1"), "-4\n");
+        assert_eq!(run("-1 >> 31"), "-1\n");
+        assert_eq!(run("240uint8 >> 4uint8"), "15\n");
+        assert_eq!(run("2147483648uint32 >> 31uint32"), "1\n");
+    }
+
+    #[test]
+    fn left_shift() {
+        assert_eq!(run("1 << 31"), "-2147483648\n");
+        assert_eq!(run("1uint128 << 100uint128"), "1267650600228229401496703205376\n");
+    }
+
+    #[test]
+    fn remainder_follows_signedness() {
+        assert_eq!(run("7 % 3"), "1\n");
+        assert_eq!(run("-7 % 3"), "-1\n");
+        assert_eq!(run("7 % -3"), "1\n");
+        assert_eq!(run("255uint8 % 7uint8"), "3\n");
+        assert_eq!(run("4294967295uint32 % 10uint32"), "5\n");
+    }
+
+    #[test]
+    fn float_remainder() {
+        assert_eq!(run("7.5 % 2.0"), "1.5\n");
+        assert_eq!(run("-7.5 % 2.0"), "-1.5\n");
+    }
+
+    #[test]
+    fn integer_casts() {
+        assert_eq!(run("300 as uint8"), "44\n");
+        assert_eq!(run("-1 as uint8"), "255\n");
+        assert_eq!(run("-1int8 as uint32"), "4294967295\n");
+        assert_eq!(run("255uint8 as int32"), "255\n");
+        assert_eq!(run("true as int32 + 1"), "2\n");
+    }
+
+    #[test]
+    fn float_casts() {
+        assert_eq!(run("1.5 as float32"), "1.5\n");
+        assert_eq!(run("-7 as float64 / 2.0"), "-3.5\n");
+        assert_eq!(run("-3.9 as int32"), "-3\n");
+    }
+
+    #[test]
+    fn float_to_int_casts_saturate() {
+        assert_eq!(run("10000000000.0 as int32"), "2147483647\n");
+        assert_eq!(run("-10000000000.0 as int32"), "-2147483648\n");
+        assert_eq!(run("300.7 as uint8"), "255\n");
+        assert_eq!(run("-1.0 as uint8"), "0\n");
+        assert_eq!(run("let zero = 0.0; (zero / zero) as int32"), "0\n");
+    }
+
+    #[test]
+    fn untagged_literals_follow_context() {
+        assert_eq!(run("let x: uint64 = 18446744073709551615; x"), "18446744073709551615\n");
+        assert_eq!(run("let x = 200uint8; x + 55"), "255\n");
+        assert_eq!(run("fn f(a: int64) -> int64 { a * 2 } f(4000000000)"), "8000000000\n");
+        assert_eq!(run("let x: float32 = 0.5; x * 4.0"), "2\n");
+        assert_eq!(run("let x: int8 = -128; x"), "-128\n");
+    }
+
+    #[test]
+    fn untagged_literals_default() {
+        assert_eq!(run("7 / 2"), "3\n");
+        assert_eq!(run("7.0 / 2.0"), "3.5\n");
+        assert_eq!(run("1 << 31"), "-2147483648\n");
+    }
+
+    #[test]
+    fn overflow_panics_with_checks() {
+        assert_eq!(run("let x: int32 = 2147483647; x + 1"), "panicked at 1:28: attempt to add with overflow");
+        assert_eq!(run("let x = 0uint8; x - 1uint8"), "panicked at 1:17: attempt to subtract with overflow");
+        assert_eq!(run("let x: int8 = -128; -x"), "panicked at 1:21: attempt to negate with overflow");
+        assert_eq!(run("let x = 2; x ** 31"), "panicked at 1:12: attempt to multiply with overflow");
+        assert_eq!(run("let x = 1; let y = 32; x << y"), "panicked at 1:24: attempt to shift left with overflow");
+        assert_eq!(run("let x = 1; let y = -1; x >> y"), "panicked at 1:24: attempt to shift right with overflow");
+    }
+
+    #[test]
+    fn overflow_wraps_without_checks() {
+        let off = || vec![OptionArg::OverflowChecks(false)];
+        assert_eq!(run_with("let x: int32 = 2147483647; x + 1", off()), "-2147483648\n");
+        assert_eq!(run_with("let x: int8 = -128; -x", off()), "-128\n");
+        assert_eq!(run_with("let x = 1; let y = 33; x << y", off()), "2\n");
+        assert_eq!(run_with("let x = -8; let y = 65; x >> y", off()), "-4\n");
+    }
+
+    #[test]
+    fn division_by_zero_panics_without_checks() {
+        let off = vec![OptionArg::OverflowChecks(false)];
+        assert_eq!(run_with("let x = 0; 1 / x", off), "panicked at 1:12: attempt to divide by zero");
+    }
 }
@@ -0,0 +1,142 @@
+//! The module for writing the compiled module to a file, linking it into an executable by default.
+
+use std::path::Path;
+use std::process::Command;
+use inkwell::passes::PassBuilderOptions;
+use inkwell::targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine};
+use inkwell::OptimizationLevel;
+use crate::args::{ArgContext, EmitKind, OptLevel};
+use crate::compiler::Compiler;
+
+/// The runtime built from `runtime/std.rs` by `build.rs`, linked into executables unless `--std` names another library.
+const BUNDLED_STD: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libiranta_std.a"));
+
+/// The system libraries the bundled runtime needs, as listed by rustc.
+const BUNDLED_STD_NATIVE_LIBS: &str = env!("IRANTA_STD_NATIVE_LIBS");
+
+impl<'ctx> Compiler<'ctx> {
+    /// Optimize the module at the level chosen by `-O`, then write it in the form chosen by `--emit`.
+    pub fn write_output(&self, arg_context: &ArgContext) -> Result<(), String> {
+        let machine = self.optimize(arg_context.opt_level())?;
+
+        let path = Path::new(&self.output_file);
+        match arg_context.emit_kind() {
+            EmitKind::Mir => unreachable!("The MIR is written before code generation."),
+            EmitKind::LlvmIr => self.module.print_to_file(path.with_extension("ll"))
+                .map_err(|err| format!("Cannot write to output file: {}", err)),
+            EmitKind::LlvmBc => {
+                let output = path.with_extension("bc");
+                if self.module.write_bitcode_to_path(&output) {
+                    Ok(())
+                } else {
+                    Err(format!("Cannot write to output file: {}", output.display()))
+                }
+            }
+            EmitKind::Asm => self.write_native(&machine, &path.with_extension("s"), FileType::Assembly),
+            EmitKind::Obj => self.write_native(&machine, &path.with_extension("o"), FileType::Object),
+            EmitKind::Exe => {
+                let temp_dir = std::env::temp_dir();
+                let object = temp_dir.join(format!("iranta-{}.o", std::process::id()));
+                let output = path.with_extension(std::env::consts::EXE_EXTENSION);
+                self.write_native(&machine, &object, FileType::Object)?;
+                let result = match arg_context.std_path() {
+                    Some(std_path) => self.link_external_std(&object, Path::new(std_path), &output),
+                    None => {
+                        let std_path = temp_dir.join(format!("iranta-{}-std.a", std::process::id()));
+                        let result = std::fs::write(&std_path, BUNDLED_STD)
+                            .map_err(|err| format!("Cannot write the bundled runtime: {}", err))
+                            .and_then(|()| Self::link(&object, &std_path, BUNDLED_STD_NATIVE_LIBS.split_whitespace(), &output));
+                        let _ = std::fs::remove_file(&std_path);
+                        result
+                    }
+                };
+                let _ = std::fs::remove_file(&object);
+                result
+            }
+        }
+    }
+
+    /// Run the optimization passes of `opt_level`, giving back the target machine of the host for code generation.
+    pub(super) fn optimize(&self, opt_level: Option<OptLevel>) -> Result<TargetMachine, String> {
+        let machine = Self::create_target_machine(opt_level)?;
+        self.module.set_triple(&machine.get_triple());
+        self.module.set_data_layout(&machine.get_target_data().get_data_layout());
+
+        // Without `-O`, the module is left as generated and only the code generator optimizes.
+        let passes = match opt_level {
+            None | Some(OptLevel::O0) => None,
+            Some(OptLevel::O1) => Some("default<O1>"),
+            Some(OptLevel::O2) => Some("default<O2>"),
+            Some(OptLevel::O3) => Some("default<O3>"),
+            Some(OptLevel::Os) => Some("default<Os>"),
+        };
+        if let Some(passes) = passes {
+            self.module.run_passes(passes, &machine, PassBuilderOptions::create())
+                .map_err(|err| format!("Cannot optimize the program: {}", err))?;
+        }
+        Ok(machine)
+    }
+
+    /// Create the target machine of the host, generating code at the given optimization level.
+    fn create_target_machine(opt_level: Option<OptLevel>) -> Result<TargetMachine, String> {
+        Target::initialize_native(&InitializationConfig::default())
+            .map_err(|err| format!("Cannot initialize the native target: {}", err))?;
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple).map_err(|err| format!("Unsupported target {}: {}", triple, err))?;
+        target.create_target_machine(
+            &triple,
+            &TargetMachine::get_host_cpu_name().to_string(),
+            &TargetMachine::get_host_cpu_features().to_string(),
+            match opt_level {
+                Some(OptLevel::O0) => OptimizationLevel::None,
+                Some(OptLevel::O1) => OptimizationLevel::Less,
+                None | Some(OptLevel::O2 | OptLevel::Os) => OptimizationLevel::Default,
+                Some(OptLevel::O3) => OptimizationLevel::Aggressive,
+            },
+            RelocMode::PIC,
+            CodeModel::Default,
+        ).ok_or_else(|| format!("Cannot create a target machine for {}.", triple))
+    }
+
+    /// Generate native code with the target machine.
+    fn write_native(&self, machine: &TargetMachine, output: &Path, file_type: FileType) -> Result<(), String> {
+        machine.write_to_file(&self.module, file_type, output)
+            .map_err(|err| format!("Cannot write to output file: {}", err))
+    }
+
+    /// Link an object file against the `--std` library, naming the runtime functions it must define if linking fails.
+    fn link_external_std(&self, object: &Path, std_path: &Path, output: &Path) -> Result<(), String> {
+        if !std_path.is_file() {
+            return Err(format!("Cannot find the standard library at \"{}\".", std_path.display()));
+        }
+        Self::link(object, std_path, BUNDLED_STD_NATIVE_LIBS.split_whitespace(), output).map_err(|err| {
+            let symbols = self.module.get_functions()
+                .filter(|function| function.count_basic_blocks() == 0)
+                .map(|function| function.get_name().to_string_lossy().into_owned())
+                .filter(|name| name.starts_with("std_"))
+                .collect::<Vec<_>>();
+            format!("{} The standard library at \"{}\" must define {}.", err, std_path.display(), symbols.join(", "))
+        })
+    }
+
+    /// Link an object file against a standard library and its system libraries with `cc` or the one named by `CC`.
+    fn link<'a>(object: &Path, std_path: &Path, libs: impl IntoIterator<Item = &'a str>, output: &Path) -> Result<(), String> {
+        let linker = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let status = Command::new(&linker)
+            .arg(object)
+            .arg(std_path)
+            .args(libs)
+            .arg("-o")
+            .arg(output)
+            .status()
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::NotFound => format!("Cannot find the linker \"{}\". Install a C toolchain or set CC to its path.", linker),
+                _ => format!("Cannot run the linker \"{}\": {}", linker, err),
+            })?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("The linker \"{}\" failed with {}.", linker, status))
+        }
+    }
+}
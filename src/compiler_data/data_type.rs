@@ -55,6 +55,10 @@ impl TypeInterner {
                     name: "int128".to_string(),
                     desc: TypeDesc::Raw,
                 },
+                TypeId::UINT128 => DataType {
+                    name: "uint128".to_string(),
+                    desc: TypeDesc::Raw,
+                },
                 TypeId::FLOAT32 => DataType {
                     name: "float32".to_string(),
                     desc: TypeDesc::Raw,
@@ -63,10 +67,80 @@ impl TypeInterner {
                     name: "float64".to_string(),
                     desc: TypeDesc::Raw,
                 },
+                TypeId::BOOL => DataType {
+                    name: "bool".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::NEVER => DataType {
+                    name: "never".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::INT_LITERAL => DataType {
+                    name: "{integer}".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::FLOAT_LITERAL => DataType {
+                    name: "{float}".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::ERROR => DataType {
+                    name: "{error}".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::CHAR => DataType {
+                    name: "char".to_string(),
+                    desc: TypeDesc::Raw,
+                },
+                TypeId::STR => DataType {
+                    name: "str".to_string(),
+                    desc: TypeDesc::Raw,
+                },
             },
         }
     }
 
+    /// Find a type by its name.
+    #[must_use]
+    pub fn find_type(&self, name: &str) -> Option<TypeId> {
+        self.to_data_type.iter().find(|(_, data_type)| data_type.name == name).map(|(type_id, _)| *type_id)
+    }
+
+    /// Find the type of references to `target`, creating it on first use.
+    #[must_use]
+    pub fn ref_type(&mut self, target: TypeId, mutable: bool) -> TypeId {
+        let found = self.to_data_type.iter().find(|(_, data_type)| {
+            matches!(data_type.desc, TypeDesc::Ref { target: found_target, mutable: found_mutable } if found_target == target && found_mutable == mutable)
+        });
+        match found {
+            Some((type_id, _)) => *type_id,
+            None => {
+                let name = format!("&{}{}", if mutable { "mut " } else { "" }, self.to_data_type[&target]);
+                self.new_type(DataType {
+                    name,
+                    desc: TypeDesc::Ref { target, mutable },
+                })
+            }
+        }
+    }
+
+    /// The type a reference type points to and whether the reference is mutable, or `None` for other types.
+    #[must_use]
+    pub fn ref_target(&self, type_id: TypeId) -> Option<(TypeId, bool)> {
+        match self.to_data_type[&type_id].desc {
+            TypeDesc::Ref { target, mutable } => Some((target, mutable)),
+            _ => None,
+        }
+    }
+
+    /// Whether values of the type are copied when used rather than moved, which shared references are as well.
+    #[must_use]
+    pub fn is_copy(&self, type_id: TypeId) -> bool {
+        match self.to_data_type[&type_id].desc {
+            TypeDesc::Ref { mutable, .. } => !mutable,
+            _ => type_id.is_copy(),
+        }
+    }
+
     #[must_use]
     pub fn new_type(&mut self, data_type: DataType) -> TypeId {
         self.type_cnt += 1;
@@ -85,6 +159,11 @@ pub enum TypeDesc {
     Raw,
     Struct,
     Enum,
+    /// A reference to a value of `target`, which can be changed through it if `mutable` is set.
+    Ref {
+        target: TypeId,
+        mutable: bool,
+    },
 }
 
 impl Display for DataType {
@@ -110,6 +189,25 @@ impl TypeId {
     pub const UINT128: Self = Self(10);
     pub const FLOAT32: Self = Self(11);
     pub const FLOAT64: Self = Self(12);
+    /// The type of expressions that never produce a value, such as a block ending with `return`.
+    pub const NEVER: Self = Self(13);
+    pub const BOOL: Self = Self(14);
+    /// The pseudo-type of untagged integer literals, resolved by the surrounding context or `int32` by default.
+    pub const INT_LITERAL: Self = Self(15);
+    /// The pseudo-type of untagged float literals, resolved by the surrounding context or `float64` by default.
+    pub const FLOAT_LITERAL: Self = Self(16);
+    /// The type of expressions with a type error, which never causes further errors.
+    pub const ERROR: Self = Self(17);
+    /// A Unicode scalar value, stored in 32 bits.
+    pub const CHAR: Self = Self(18);
+    /// An immutable UTF-8 string, stored as a pointer to its bytes and its length in bytes.
+    pub const STR: Self = Self(19);
+
+    const BUILTIN_MAX: u32 = 19;
 
-    const BUILTIN_MAX: u32 = 12;
+    /// Whether values of the type are copied when used rather than moved, as for every builtin type but `str`.
+    #[must_use]
+    pub fn is_copy(self) -> bool {
+        self != Self::STR && self.0 <= Self::BUILTIN_MAX
+    }
 }
@@ -3,3 +3,4 @@
 pub mod position;
 pub mod value;
 pub mod data_type;
+pub mod symbol;
@@ -3,7 +3,7 @@
 use std::fmt::{Display, Formatter};
 
 #[cfg_attr(debug_assertions, derive(Debug))]
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct Position {
     pub start_line: usize,
     pub end_line: usize,
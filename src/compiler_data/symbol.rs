@@ -0,0 +1,53 @@
+//! The module of `Symbol`
+
+use std::collections::HashMap;
+
+/// Interns identifier names so that later stages compare symbols instead of strings.
+pub struct SymbolInterner {
+    to_symbol: HashMap<String, Symbol>,
+    to_name: Vec<String>,
+}
+
+impl SymbolInterner {
+    #[must_use]
+    pub fn create() -> Self {
+        Self {
+            to_symbol: HashMap::new(),
+            to_name: vec![],
+        }
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.to_symbol.get(name) {
+            return *symbol;
+        }
+        let symbol = Symbol(self.to_name.len() as u32);
+        self.to_symbol.insert(name.to_string(), symbol);
+        self.to_name.push(name.to_string());
+        symbol
+    }
+
+    #[must_use]
+    pub fn get_name(&self, symbol: Symbol) -> &str {
+        &self.to_name[symbol.0 as usize]
+    }
+}
+
+#[derive(Clone, Copy, PartialOrd, PartialEq, Ord, Eq, Hash, Debug)]
+pub struct Symbol(u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning_is_idempotent() {
+        let mut symbols = SymbolInterner::create();
+        let foo = symbols.intern("foo");
+        let bar = symbols.intern("bar");
+        assert_eq!(symbols.intern("foo"), foo);
+        assert_ne!(foo, bar);
+        assert_eq!(symbols.get_name(foo), "foo");
+        assert_eq!(symbols.get_name(bar), "bar");
+    }
+}
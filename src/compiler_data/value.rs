@@ -2,11 +2,16 @@
 
 use std::fmt::Display;
 
+#[derive(Clone)]
 pub enum Value {
     Integer(ValueInteger),
     Float(ValueFloat),
+    Bool(bool),
+    Char(char),
+    Str(String),
 }
 
+#[derive(Clone)]
 pub enum ValueInteger {
     Int8(i8),
     UInt8(u8),
@@ -18,11 +23,14 @@ pub enum ValueInteger {
     UInt64(u64),
     Int128(i128),
     UInt128(u128),
+    Untyped(u128),
 }
 
+#[derive(Clone)]
 pub enum ValueFloat {
     Float32(f32),
     Float64(f64),
+    Untyped(f64),
 }
 
 impl Display for Value {
@@ -41,14 +49,19 @@ impl Display for Value {
                         ValueInteger::UInt64(int) => format!("uint64({})", int),
                         ValueInteger::Int128(int) => format!("int128({})", int),
                         ValueInteger::UInt128(int) => format!("uint128({})", int),
+                        ValueInteger::Untyped(int) => format!("{{integer}}({})", int),
                     }
                 }
                 Self::Float(float) => {
                     match float {
                         ValueFloat::Float32(float) => format!("float32({})", float),
                         ValueFloat::Float64(float) => format!("float64({})", float),
+                        ValueFloat::Untyped(float) => format!("{{float}}({})", float),
                     }
                 }
+                Self::Bool(boolean) => format!("bool({})", boolean),
+                Self::Char(ch) => format!("char({:?})", ch),
+                Self::Str(string) => format!("str({:?})", string),
             }
         )
     }
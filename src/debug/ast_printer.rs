@@ -1,23 +1,202 @@
 //! The module of `AstPrinter`
 
-use crate::ast::expr::{Expr, ExprBinary, ExprLiteral, ExprType, ExprUnary};
+use crate::ast::expr::{Expr, ExprBinary, ExprBlock, ExprBorrow, ExprCall, ExprCast, ExprDeref, ExprIf, ExprLiteral, ExprLoop, ExprType, ExprUnary, ExprVariable, ExprWhile};
+use crate::ast::program::Program;
+use crate::ast::stmt::{Stmt, StmtAssign, StmtBreak, StmtContinue, StmtExpr, StmtFn, StmtLet, StmtReturn, StmtType};
+use crate::compiler_data::symbol::Symbol;
+use crate::ast::type_expr::{TypeExpr, TypeExprType};
 use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::SymbolInterner;
 
-pub struct AstPrinter {}
+pub struct AstPrinter<'a> {
+    symbols: &'a SymbolInterner,
+}
+
+impl<'a> AstPrinter<'a> {
+    #[must_use]
+    pub fn new(symbols: &'a SymbolInterner) -> Self {
+        Self { symbols }
+    }
+
+    #[must_use]
+    pub fn print_program(&self, program: &Program) -> String {
+        let mut out = String::new();
+        for stmt in &program.stmts {
+            out.push_str(&self.print_stmt(stmt));
+            out.push('\n');
+        }
+        if let Some(tail) = &program.tail {
+            out.push_str(&self.print_expr(tail));
+            out.push('\n');
+        }
+        out.trim_end().to_string()
+    }
+
+    #[must_use]
+    pub fn print_stmt(&self, stmt: &Stmt) -> String {
+        self.indent(
+            &match &stmt.stmt_type {
+                StmtType::Let(stmt_let) => self.print_stmt_let(&stmt.pos, stmt_let),
+                StmtType::Assign(assign) => self.print_stmt_assign(&stmt.pos, assign),
+                StmtType::Expr(stmt_expr) => self.print_stmt_expr(&stmt.pos, stmt_expr),
+                StmtType::Fn(stmt_fn) => self.print_stmt_fn(&stmt.pos, stmt_fn),
+                StmtType::Return(stmt_return) => self.print_stmt_return(&stmt.pos, stmt_return),
+                StmtType::Break(stmt_break) => self.print_stmt_break(&stmt.pos, stmt_break),
+                StmtType::Continue(stmt_continue) => self.print_stmt_continue(&stmt.pos, stmt_continue),
+            }
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_let(&self, pos: &Position, stmt_let: &StmtLet) -> String {
+        format!(
+            "{} Stmt::Let {{\n\
+                name: {}\n\
+                mutable: {}\n\
+                var_type: {}\n\
+                init: {}\n\
+            }}",
+            pos,
+            self.symbols.get_name(stmt_let.name),
+            stmt_let.mutable,
+            match &stmt_let.var_type {
+                Some(var_type) => self.print_type(var_type),
+                None => "None".to_string(),
+            },
+            self.print_expr(&stmt_let.init),
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_assign(&self, pos: &Position, assign: &StmtAssign) -> String {
+        format!(
+            "{} Stmt::Assign {{\n\
+                ope: {}\n\
+                target: {}\n\
+                value: {}\n\
+            }}",
+            pos,
+            match &assign.ope {
+                Some(ope) => format!("{}=", ope),
+                None => "=".to_string(),
+            },
+            self.print_expr(&assign.target),
+            self.print_expr(&assign.value),
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_expr(&self, pos: &Position, stmt_expr: &StmtExpr) -> String {
+        format!(
+            "{} Stmt::Expr {{\n\
+                expr: {}\n\
+            }}",
+            pos,
+            self.print_expr(&stmt_expr.expr),
+        )
+    }
 
-impl AstPrinter {
     #[must_use]
-    pub fn new() -> Self {
-        Self {}
+    fn print_stmt_fn(&self, pos: &Position, stmt_fn: &StmtFn) -> String {
+        let params: Vec<String> = stmt_fn.params.iter().map(|param| {
+            format!(
+                "{} {}{}: {}",
+                param.pos,
+                if param.mutable { "mut " } else { "" },
+                self.symbols.get_name(param.name),
+                self.print_type(&param.param_type),
+            )
+        }).collect();
+        format!(
+            "{} Stmt::Fn {{\n\
+                name: {}\n\
+                params: [{}]\n\
+                return_type: {}\n\
+                body: {}\n\
+            }}",
+            pos,
+            self.symbols.get_name(stmt_fn.name),
+            params.join(", "),
+            match &stmt_fn.return_type {
+                Some(return_type) => self.print_type(return_type),
+                None => "None".to_string(),
+            },
+            self.print_expr(&stmt_fn.body),
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_return(&self, pos: &Position, stmt_return: &StmtReturn) -> String {
+        format!(
+            "{} Stmt::Return {{\n\
+                value: {}\n\
+            }}",
+            pos,
+            match &stmt_return.value {
+                Some(value) => self.print_expr(value),
+                None => "None".to_string(),
+            },
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_break(&self, pos: &Position, stmt_break: &StmtBreak) -> String {
+        format!(
+            "{} Stmt::Break {{\n\
+                label: {}\n\
+                value: {}\n\
+            }}",
+            pos,
+            self.print_label(stmt_break.label),
+            match &stmt_break.value {
+                Some(value) => self.print_expr(value),
+                None => "None".to_string(),
+            },
+        )
+    }
+
+    #[must_use]
+    fn print_stmt_continue(&self, pos: &Position, stmt_continue: &StmtContinue) -> String {
+        format!(
+            "{} Stmt::Continue {{ label: {} }}",
+            pos,
+            self.print_label(stmt_continue.label),
+        )
+    }
+
+    #[must_use]
+    fn print_label(&self, label: Option<Symbol>) -> String {
+        match label {
+            Some(label) => format!("'{}", self.symbols.get_name(label)),
+            None => "None".to_string(),
+        }
+    }
+
+    #[must_use]
+    fn print_type(&self, type_expr: &TypeExpr) -> String {
+        match &type_expr.type_expr_type {
+            TypeExprType::Named(name) => format!("{} {}", type_expr.pos, self.symbols.get_name(*name)),
+            TypeExprType::Ref { mutable, target } => format!("{} &{}({})", type_expr.pos, if *mutable { "mut " } else { "" }, self.print_type(target)),
+        }
     }
 
     #[must_use]
     pub fn print_expr(&self, expr: &Expr) -> String {
         self.indent(
             &match &expr.expr_type {
-                ExprType::Literal(literal) => self.print_expr_literal(&expr.pos, &literal),
-                ExprType::Unary(unary) => self.print_expr_unary(&expr.pos, &unary),
-                ExprType::Binary(binary) => self.print_expr_binary(&expr.pos, &binary),
+                ExprType::Literal(literal) => self.print_expr_literal(&expr.pos, literal),
+                ExprType::Unary(unary) => self.print_expr_unary(&expr.pos, unary),
+                ExprType::Binary(binary) => self.print_expr_binary(&expr.pos, binary),
+                ExprType::Variable(variable) => self.print_expr_variable(&expr.pos, variable),
+                ExprType::Block(block) => self.print_expr_block(&expr.pos, block),
+                ExprType::Call(call) => self.print_expr_call(&expr.pos, call),
+                ExprType::If(expr_if) => self.print_expr_if(&expr.pos, expr_if),
+                ExprType::While(expr_while) => self.print_expr_while(&expr.pos, expr_while),
+                ExprType::Loop(expr_loop) => self.print_expr_loop(&expr.pos, expr_loop),
+                ExprType::Cast(cast) => self.print_expr_cast(&expr.pos, cast),
+                ExprType::Borrow(borrow) => self.print_expr_borrow(&expr.pos, borrow),
+                ExprType::Deref(deref) => self.print_expr_deref(&expr.pos, deref),
+                ExprType::Error => format!("{} Expr::Error", expr.pos),
             }
         )
     }
@@ -31,6 +210,129 @@ impl AstPrinter {
         )
     }
 
+    #[must_use]
+    fn print_expr_variable(&self, pos: &Position, variable: &ExprVariable) -> String {
+        format!(
+            "{} Expr::Variable {{ name: {} }} ",
+            pos,
+            self.symbols.get_name(variable.name)
+        )
+    }
+
+    #[must_use]
+    fn print_expr_block(&self, pos: &Position, block: &ExprBlock) -> String {
+        let stmts: Vec<String> = block.stmts.iter().map(|stmt| self.print_stmt(stmt)).collect();
+        format!(
+            "{} Expr::Block {{\n\
+                stmts: [\n{}\n]\n\
+                tail: {}\n\
+            }}",
+            pos,
+            stmts.join("\n"),
+            match &block.tail {
+                Some(tail) => self.print_expr(tail),
+                None => "None".to_string(),
+            },
+        )
+    }
+
+    #[must_use]
+    fn print_expr_cast(&self, pos: &Position, cast: &ExprCast) -> String {
+        format!(
+            "{} Expr::Cast {{\n\
+                expr: {}\n\
+                target: {}\n\
+            }}",
+            pos,
+            self.print_expr(&cast.expr),
+            self.print_type(&cast.target),
+        )
+    }
+
+    #[must_use]
+    fn print_expr_borrow(&self, pos: &Position, borrow: &ExprBorrow) -> String {
+        format!(
+            "{} Expr::Borrow {{\n\
+                mutable: {}\n\
+                expr: {}\n\
+            }}",
+            pos,
+            borrow.mutable,
+            self.print_expr(&borrow.expr),
+        )
+    }
+
+    #[must_use]
+    fn print_expr_deref(&self, pos: &Position, deref: &ExprDeref) -> String {
+        format!(
+            "{} Expr::Deref {{\n\
+                expr: {}\n\
+            }}",
+            pos,
+            self.print_expr(&deref.expr),
+        )
+    }
+
+    #[must_use]
+    fn print_expr_call(&self, pos: &Position, call: &ExprCall) -> String {
+        let args: Vec<String> = call.args.iter().map(|arg| self.print_expr(arg)).collect();
+        format!(
+            "{} Expr::Call {{\n\
+                callee: {}\n\
+                args: [\n{}\n]\n\
+            }}",
+            pos,
+            self.print_expr(&call.callee),
+            args.join("\n"),
+        )
+    }
+
+    #[must_use]
+    fn print_expr_if(&self, pos: &Position, expr_if: &ExprIf) -> String {
+        format!(
+            "{} Expr::If {{\n\
+                cond: {}\n\
+                then_branch: {}\n\
+                else_branch: {}\n\
+            }}",
+            pos,
+            self.print_expr(&expr_if.cond),
+            self.print_expr(&expr_if.then_branch),
+            match &expr_if.else_branch {
+                Some(else_branch) => self.print_expr(else_branch),
+                None => "None".to_string(),
+            },
+        )
+    }
+
+    #[must_use]
+    fn print_expr_while(&self, pos: &Position, expr_while: &ExprWhile) -> String {
+        format!(
+            "{} Expr::While {{\n\
+                label: {}\n\
+                cond: {}\n\
+                body: {}\n\
+            }}",
+            pos,
+            self.print_label(expr_while.label),
+            self.print_expr(&expr_while.cond),
+            self.print_expr(&expr_while.body),
+        )
+    }
+
+    #[must_use]
+    fn print_expr_loop(&self, pos: &Position, expr_loop: &ExprLoop) -> String {
+        format!(
+            "{} Expr::Loop {{\n\
+                label: {}\n\
+                body: {}\n\
+            }}",
+            pos,
+            self.print_label(expr_loop.label),
+            self.print_expr(&expr_loop.body),
+        )
+    }
+
     #[must_use]
     fn print_expr_unary(&self, pos: &Position, unary: &ExprUnary) -> String {
         format!(
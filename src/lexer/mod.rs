@@ -1,10 +1,13 @@
 //! The module of `Lexer`
 
+use std::collections::HashMap;
 use std::str::FromStr;
+use maplit::hashmap;
 use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::SymbolInterner;
 use crate::lexer::context::LexerContext;
 use crate::lexer::error::{LexicalError, LexicalResult};
-use crate::lexer::token::{Token, TokenFloat, TokenInteger, TokenLiteral, TokenOperator, TokenParen, TokenType};
+use crate::lexer::token::{Token, TokenAssign, TokenFloat, TokenInteger, TokenKeyword, TokenLiteral, TokenOperator, TokenParen, TokenPunctuation, TokenType};
 
 pub mod token;
 pub mod context;
@@ -13,33 +16,66 @@ pub mod error;
 pub struct Lexer {
     chars: Vec<char>,
     line: usize,
+    start_line: usize,
     start_col: usize,
     current_col: usize,
     current: usize,
     buf_token: Option<Token>,
+    keywords: HashMap<&'static str, TokenKeyword>,
+    symbols: SymbolInterner,
 }
 
 impl Lexer {
     #[must_use]
-    pub fn new(source: &String) -> Self {
+    pub fn new(source: &str, symbols: SymbolInterner) -> Self {
         let chars: Vec<char> = source.chars().collect();
 
         Self {
             chars,
             line: 1,
+            start_line: 1,
             start_col: 1,
             current_col: 1,
             current: 0,
             buf_token: None,
+            keywords: Self::init_keywords(),
+            symbols,
         }
     }
 
+    #[must_use]
+    fn init_keywords() -> HashMap<&'static str, TokenKeyword> {
+        hashmap! {
+            "let" => TokenKeyword::Let,
+            "mut" => TokenKeyword::Mut,
+            "fn" => TokenKeyword::Fn,
+            "if" => TokenKeyword::If,
+            "else" => TokenKeyword::Else,
+            "while" => TokenKeyword::While,
+            "loop" => TokenKeyword::Loop,
+            "break" => TokenKeyword::Break,
+            "continue" => TokenKeyword::Continue,
+            "return" => TokenKeyword::Return,
+            "true" => TokenKeyword::True,
+            "false" => TokenKeyword::False,
+            "as" => TokenKeyword::As,
+        }
+    }
+
+    /// Give back the symbol interner holding every identifier seen so far.
+    #[must_use]
+    pub fn into_symbols(self) -> SymbolInterner {
+        self.symbols
+    }
+
+    #[must_use]
+    pub fn get_symbols(&self) -> &SymbolInterner {
+        &self.symbols
+    }
+
     pub fn peek_token(&mut self, ctx: &LexerContext) -> LexicalResult<&Token> {
         if self.buf_token.is_none() {
-            let token = self.scan_token(ctx).or_else(|err| {
-                self.synchronize();
-                Err(err)
-            })?;
+            let token = self.scan_token(ctx).inspect_err(|_| self.synchronize())?;
             self.buf_token = Some(token);
         }
 
@@ -48,94 +84,228 @@ impl Lexer {
 
     pub fn get_token(&mut self, ctx: &LexerContext) -> LexicalResult<Token> {
         if self.buf_token.is_none() {
-            let token = self.scan_token(ctx).or_else(|err| {
-                self.synchronize();
-                Err(err)
-            })?;
+            let token = self.scan_token(ctx).inspect_err(|_| self.synchronize())?;
             self.buf_token = Some(token);
         }
 
         let token = self.buf_token.take().unwrap();
         if ctx.debug_print_token {
-            println!("{}", token);
+            match &token.token_type {
+                TokenType::Identifier(symbol) => println!("{} \"{}\"", token, self.symbols.get_name(*symbol)),
+                _ => println!("{}", token),
+            }
         }
         Ok(token)
     }
 
     fn scan_token(&mut self, ctx: &LexerContext) -> LexicalResult<Token> {
-        self.skip_whitespace();
+        self.skip_whitespace()?;
         self.set_col();
 
         match self.advance() {
-            '\0' => Ok(self.make_token(TokenType::EOF)),
-            '+' => Ok(self.make_token(TokenType::Operator(TokenOperator::Plus))),
-            '-' => Ok(self.make_token(TokenType::Operator(TokenOperator::Minus))),
+            '\0' => Ok(self.make_token(TokenType::Eof)),
+            '+' => {
+                let token_type = if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::PlusAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::Plus)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '-' => {
+                let token_type = if self.match_advance('>') {
+                    TokenType::Punctuation(TokenPunctuation::Arrow)
+                } else if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::MinusAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::Minus)
+                };
+                Ok(self.make_token(token_type))
+            }
             '*' => {
-                let ope = if self.match_advance('*') {
-                    TokenOperator::Power
+                let token_type = if self.match_advance('*') {
+                    if self.match_advance('=') {
+                        TokenType::Assign(TokenAssign::PowerAssign)
+                    } else {
+                        TokenType::Operator(TokenOperator::Power)
+                    }
+                } else if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::MultiplyAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::Multiply)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '/' => {
+                let token_type = if self.match_advance('/') {
+                    // Plain line comments have been skipped already, so this is a `///` doc comment.
+                    self.advance();
+                    let mut text = String::new();
+                    while !matches!(self.peek_next(), '\0' | '\n') {
+                        text.push(self.advance());
+                    }
+                    TokenType::DocComment(text.trim_end().to_string())
+                } else if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::DivideAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::Divide)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '%' => {
+                let token_type = if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::RemainderAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::Remainder)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '=' => {
+                let token_type = if self.match_advance('=') {
+                    TokenType::Operator(TokenOperator::Equal)
                 } else {
-                    TokenOperator::Multiply
+                    TokenType::Assign(TokenAssign::Assign)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '!' => {
+                let ope = if self.match_advance('=') {
+                    TokenOperator::NotEqual
+                } else {
+                    TokenOperator::Not
                 };
                 Ok(self.make_token(TokenType::Operator(ope)))
             }
-            '/' => Ok(self.make_token(TokenType::Operator(TokenOperator::Divide))),
+            '<' => {
+                let token_type = if self.match_advance('<') {
+                    if self.match_advance('=') {
+                        TokenType::Assign(TokenAssign::ShiftLeftAssign)
+                    } else {
+                        TokenType::Operator(TokenOperator::ShiftLeft)
+                    }
+                } else if self.match_advance('=') {
+                    TokenType::Operator(TokenOperator::LessEqual)
+                } else {
+                    TokenType::Operator(TokenOperator::Less)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '>' => {
+                let token_type = if self.match_advance('>') {
+                    if self.match_advance('=') {
+                        TokenType::Assign(TokenAssign::ShiftRightAssign)
+                    } else {
+                        TokenType::Operator(TokenOperator::ShiftRight)
+                    }
+                } else if self.match_advance('=') {
+                    TokenType::Operator(TokenOperator::GreaterEqual)
+                } else {
+                    TokenType::Operator(TokenOperator::Greater)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '&' => {
+                let token_type = if self.match_advance('&') {
+                    TokenType::Operator(TokenOperator::And)
+                } else if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::BitAndAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::BitAnd)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '|' => {
+                let token_type = if self.match_advance('|') {
+                    TokenType::Operator(TokenOperator::Or)
+                } else if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::BitOrAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::BitOr)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '^' => {
+                let token_type = if self.match_advance('=') {
+                    TokenType::Assign(TokenAssign::BitXorAssign)
+                } else {
+                    TokenType::Operator(TokenOperator::BitXor)
+                };
+                Ok(self.make_token(token_type))
+            }
+            '~' => Ok(self.make_token(TokenType::Operator(TokenOperator::BitNot))),
+            ';' => Ok(self.make_token(TokenType::Punctuation(TokenPunctuation::Semicolon))),
+            ':' => Ok(self.make_token(TokenType::Punctuation(TokenPunctuation::Colon))),
+            ',' => Ok(self.make_token(TokenType::Punctuation(TokenPunctuation::Comma))),
             '(' => Ok(self.make_token(TokenType::Paren(TokenParen::LeftParen))),
             ')' => Ok(self.make_token(TokenType::Paren(TokenParen::RightParen))),
+            '{' => Ok(self.make_token(TokenType::Paren(TokenParen::LeftBrace))),
+            '}' => Ok(self.make_token(TokenType::Paren(TokenParen::RightBrace))),
             '.' => self.scan_number(ctx),
-            ch if ch.is_digit(10) => self.scan_number(ctx),
+            ch if ch.is_ascii_digit() => self.scan_number(ctx),
+            '"' => self.scan_string(ctx),
+            'r' if matches!(self.peek_next(), '"' | '#') => self.scan_raw_string(ctx),
+            ch if ch == '_' || unicode_ident::is_xid_start(ch) => Ok(self.scan_identifier(ctx)),
+            // `'a'` and `'\n'` are characters, while `'a` without a closing quote is a label.
+            '\'' if self.peek_next() == '\\' || self.peek_next() == '\'' || self.peek_at(1) == '\'' => self.scan_char(ctx),
+            '\'' => self.scan_label(ctx),
             _ => Err(LexicalError::new(self.get_current_pos(), "Invalid character.".to_string())),
         }
     }
 
+    /// Scan a number whose first digit or '.' has been consumed.
+    ///
+    /// Numbers may have a `0b`, `0o` or `0x` radix prefix, `_` separators, a fraction, an exponent written `e` in
+    /// decimal or `p` (a power of two) in hexadecimal, and a type tag such as `int8` or its short form `i8`.
     fn scan_number(&mut self, _ctx: &LexerContext) -> LexicalResult<Token> {
         let ch = self.last();
 
-        let mut found_dot = false;
-        let mut number_str = String::from(ch);
-        let mut tag_str = String::new();
-        let radix = if ch == '0' && self.peek_next().is_alphabetic() {
-            let next = self.peek_next();
-            match next {
-                'b' => {
-                    self.advance();
-                    2
-                },
-                'o' => {
-                    self.advance();
-                    8
-                },
-                'x' => {
-                    self.advance();
-                    16
-                },
-                _ => 10,
+        let radix = if ch == '0' && matches!(self.peek_next(), 'b' | 'o' | 'x') {
+            match self.advance() {
+                'b' => 2,
+                'o' => 8,
+                _ => 16,
             }
         } else {
             10
         };
 
-        if ch == '.' {
-            found_dot = true;
+        let mut found_dot = ch == '.';
+        let mut number_str = if radix == 10 { String::from(ch) } else { String::new() };
+        if !found_dot {
+            self.scan_digits(radix, &mut number_str)?;
+            if self.match_advance('.') {
+                found_dot = true;
+                number_str.push('.');
+            }
         }
-
-        loop {
-            let ch = self.peek_next();
-            match ch {
-                '.' => {
-                    if !found_dot {
-                        found_dot = true;
-                        number_str.push(ch);
-                    } else {
-                        return Err(LexicalError::new(self.get_current_pos(), "Unexpected dot.".to_string()))
-                    }
-                }
-                '_' => (),
-                ch if ch.is_digit(10) => number_str.push(ch),
-                _ => break,
+        if found_dot {
+            self.scan_digits(radix, &mut number_str)?;
+            if self.match_advance('.') {
+                return Err(LexicalError::new(self.get_current_pos(), "Unexpected dot.".to_string()));
             }
+        }
+
+        let mut found_exponent = false;
+        let exponent_char = match radix {
+            10 => Some('e'),
+            16 => Some('p'),
+            _ => None,
+        };
+        if exponent_char.is_some_and(|exponent_char| self.peek_next().to_ascii_lowercase() == exponent_char) {
+            found_exponent = true;
             self.advance();
+            number_str.push(exponent_char.unwrap());
+            if matches!(self.peek_next(), '+' | '-') {
+                number_str.push(self.advance());
+            }
+            let len = number_str.len();
+            self.scan_digits(10, &mut number_str)?;
+            if number_str.len() == len {
+                return Err(LexicalError::new(self.get_pos(), "Expect digits in the exponent.".to_string()));
+            }
         }
 
+        let mut tag_str = String::new();
         loop {
             let ch = self.peek_next();
             match ch {
@@ -155,135 +325,310 @@ impl Lexer {
             } else if number_str.ends_with('.') {
                 number_str.push('0');
             }
+            if radix == 16 && !found_exponent {
+                return Err(LexicalError::new(self.get_pos(), "A hexadecimal float needs a 'p' exponent.".to_string()));
+            }
         }
 
-        let token_type = match tag_str.as_str() {
-            "" => {
-                if found_dot {
-                    if radix != 10 {
-                        return Err(LexicalError::new(self.get_pos(), "Cannot use radix prefix in float type.".to_string()));
-                    }
-                    let number = f64::from_str(&number_str).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid float64 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Float(TokenFloat::Float64(number)))
-                } else {
-                    let number = i32::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int32 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int32(number)))
-                }
-            }
-            "int8" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int8 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = i8::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int8 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int8(number)))
-                }
-            }
-            "uint8" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid uint8 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = u8::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid uint8 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt8(number)))
-                }
-            }
-            "int16" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int16 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = i16::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int16 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int16(number)))
-                }
+        let tag = match tag_str.as_str() {
+            "i8" => "int8",
+            "u8" => "uint8",
+            "i16" => "int16",
+            "u16" => "uint16",
+            "i32" => "int32",
+            "u32" => "uint32",
+            "i64" => "int64",
+            "u64" => "uint64",
+            "i128" => "int128",
+            "u128" => "uint128",
+            "f32" => "float32",
+            "f64" => "float64",
+            tag => tag,
+        };
+        let is_float = found_dot || found_exponent || matches!(tag, "float32" | "float64");
+
+        if is_float {
+            if radix == 2 || radix == 8 {
+                return Err(LexicalError::new(self.get_pos(), "Cannot use radix prefix in float type.".to_string()));
             }
-            "uint16" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid uint16 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = u16::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid uint16 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt16(number)))
+            let number = match tag {
+                "" => TokenFloat::Untyped(self.parse_float(&number_str, radix, "float")?),
+                "float32" if radix == 10 => TokenFloat::Float32(number_str.parse().map_err(|err| LexicalError::new(self.get_pos(), format!("Invalid float32 number: {}", err)))?),
+                "float32" => TokenFloat::Float32(self.parse_float(&number_str, radix, "float32")? as f32),
+                "float64" => TokenFloat::Float64(self.parse_float(&number_str, radix, "float64")?),
+                _ if found_dot => return Err(LexicalError::new(self.get_pos(), format!("Invalid {} number: Unexpected dot.", tag))),
+                _ => return Err(LexicalError::new(self.get_pos(), format!("Invalid {} number: Unexpected exponent.", tag))),
+            };
+            return Ok(self.make_token(TokenType::Literal(TokenLiteral::Float(number))));
+        }
+
+        let tag_name = if tag.is_empty() { "integer" } else { tag };
+        let number = u128::from_str_radix(&number_str, radix).map_err(|err| LexicalError::new(self.get_pos(), format!("Invalid {} number: {}", tag_name, err)))?;
+        let number = match tag {
+            "" => TokenInteger::Untyped(number),
+            "int8" => TokenInteger::Int8(self.fit_int(number, tag)?),
+            "uint8" => TokenInteger::UInt8(self.fit_int(number, tag)?),
+            "int16" => TokenInteger::Int16(self.fit_int(number, tag)?),
+            "uint16" => TokenInteger::UInt16(self.fit_int(number, tag)?),
+            "int32" => TokenInteger::Int32(self.fit_int(number, tag)?),
+            "uint32" => TokenInteger::UInt32(self.fit_int(number, tag)?),
+            "int64" => TokenInteger::Int64(self.fit_int(number, tag)?),
+            "uint64" => TokenInteger::UInt64(self.fit_int(number, tag)?),
+            "int128" => TokenInteger::Int128(self.fit_int(number, tag)?),
+            "uint128" => TokenInteger::UInt128(number),
+            _ => return Err(LexicalError::new(self.get_pos(), format!("Invalid number tag: {}", tag_str))),
+        };
+
+        Ok(self.make_token(TokenType::Literal(TokenLiteral::Integer(number))))
+    }
+
+    /// Scan the digits of a number in `radix`, skipping '_' separators.
+    ///
+    /// A decimal digit too large for the radix is reported at its position, while letters are left for the tag.
+    fn scan_digits(&mut self, radix: u32, digits: &mut String) -> LexicalResult<()> {
+        loop {
+            let ch = self.peek_next();
+            match ch {
+                '_' => (),
+                ch if ch.is_digit(radix) => digits.push(ch),
+                ch if ch.is_ascii_digit() => {
+                    self.advance();
+                    return Err(LexicalError::new(self.get_current_pos(), format!("Invalid digit '{}' in a base {} number.", ch, radix)));
                 }
+                _ => return Ok(()),
             }
-            "int32" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int32 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = i32::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int32 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int32(number)))
+            self.advance();
+        }
+    }
+
+    /// Parse a decimal float, or a hexadecimal one such as `1.8p3` whose value is `0x1.8 * 2^3`.
+    fn parse_float(&self, number_str: &str, radix: u32, tag: &str) -> LexicalResult<f64> {
+        let invalid = |msg: String| LexicalError::new(self.get_pos(), format!("Invalid {} number: {}", tag, msg));
+        if radix == 10 {
+            return f64::from_str(number_str).map_err(|err| invalid(err.to_string()));
+        }
+
+        let (mantissa, exponent) = number_str.split_once('p').unwrap();
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let exponent: i32 = exponent.parse().map_err(|_| invalid("exponent too large".to_string()))?;
+        let mantissa = u128::from_str_radix(&format!("{}{}", int_part, frac_part), 16).map_err(|err| invalid(err.to_string()))?;
+        Ok(mantissa as f64 * 2f64.powi(exponent.saturating_sub(4 * frac_part.len() as i32)))
+    }
+
+    /// Convert an integer literal to the type of its tag, reporting values that do not fit.
+    fn fit_int<T: TryFrom<u128>>(&self, number: u128, tag: &str) -> LexicalResult<T> {
+        T::try_from(number).map_err(|_| LexicalError::new(self.get_pos(), format!("Invalid {} number: number too large to fit in target type", tag)))
+    }
+
+    /// Scan a string literal whose opening '"' has been consumed.
+    ///
+    /// After a bad escape sequence the string is still scanned to its end, so that lexing resumes after it.
+    fn scan_string(&mut self, _ctx: &LexerContext) -> LexicalResult<Token> {
+        let open_pos = self.get_pos();
+        let mut string = String::new();
+        let mut first_err = None;
+        loop {
+            let (line, col) = (self.line, self.current_col);
+            match self.advance() {
+                '\0' if self.current == self.chars.len() => {
+                    return Err(LexicalError::new(open_pos, "Unterminated string literal.".to_string()));
                 }
+                '"' => break,
+                '\\' => match self.scan_escape(line, col) {
+                    Ok(ch) => string.push(ch),
+                    Err(err) => {
+                        first_err.get_or_insert(err);
+                    }
+                },
+                ch => string.push(ch),
             }
-            "uint32" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid uint32 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = u32::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid uint32 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt32(number)))
+        }
+
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(self.make_token(TokenType::Literal(TokenLiteral::Str(string)))),
+        }
+    }
+
+    /// Scan a raw string such as `r"C:\path"` or `r#"say "hi""#` whose 'r' has been consumed.
+    ///
+    /// Escape sequences are kept as they are, and the string ends at a '"' followed by as many '#' as it started with.
+    fn scan_raw_string(&mut self, _ctx: &LexerContext) -> LexicalResult<Token> {
+        let mut hashes = 0;
+        while self.match_advance('#') {
+            hashes += 1;
+        }
+        if !self.match_advance('"') {
+            return Err(LexicalError::new(self.get_current_pos(), "Expect '\"' to start a raw string.".to_string()));
+        }
+        let open_pos = self.get_pos();
+
+        let mut string = String::new();
+        loop {
+            match self.advance() {
+                '\0' if self.current == self.chars.len() => {
+                    return Err(LexicalError::new(open_pos, "Unterminated raw string literal.".to_string()));
                 }
-            }
-            "int64" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int64 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = i64::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int64 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int64(number)))
+                '"' if (0..hashes).all(|offset| self.peek_at(offset) == '#') => {
+                    for _ in 0..hashes {
+                        self.advance();
+                    }
+                    break;
                 }
+                ch => string.push(ch),
             }
-            "uint64" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid uint64 number: Unexpected dot.".to_string()))
-                } else {
-                    let number = u64::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid uint64 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt64(number)))
+        }
+
+        Ok(self.make_token(TokenType::Literal(TokenLiteral::Str(string))))
+    }
+
+    /// Scan a character literal whose opening '\'' has been consumed.
+    fn scan_char(&mut self, _ctx: &LexerContext) -> LexicalResult<Token> {
+        let (line, col) = (self.line, self.current_col);
+        let ch = match self.advance() {
+            '\'' => return Err(LexicalError::new(self.get_pos(), "Empty character literal.".to_string())),
+            '\\' => self.scan_escape(line, col)?,
+            ch => ch,
+        };
+        if !self.match_advance('\'') {
+            return Err(LexicalError::new(self.get_pos(), "Expect ''' to close the character literal.".to_string()));
+        }
+        Ok(self.make_token(TokenType::Literal(TokenLiteral::Char(ch))))
+    }
+
+    /// Scan an escape sequence whose '\' at `line` and `col` has been consumed, reporting errors at the whole sequence.
+    fn scan_escape(&mut self, line: usize, col: usize) -> LexicalResult<char> {
+        let ch = match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => {
+                if !self.match_advance('{') {
+                    return Err(LexicalError::new(self.get_pos_from(line, col), "Expect '{' after '\\u'.".to_string()));
                 }
-            }
-            "int128" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int128 number: Unexpected dot found.".to_string()))
-                } else {
-                    let number = i128::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid int128 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int128(number)))
+                let mut digits = String::new();
+                while self.peek_next().is_ascii_hexdigit() {
+                    digits.push(self.advance());
                 }
-            }
-            "uint128" => {
-                if found_dot {
-                    return Err(LexicalError::new(self.get_pos(), "Invalid int8 number: Unexpected dot found.".to_string()))
-                } else {
-                    let number = u128::from_str_radix(&number_str, radix).or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid uint128 number: {}", err))))?;
-                    TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt128(number)))
+                if !self.match_advance('}') {
+                    return Err(LexicalError::new(self.get_pos_from(line, col), "Expect '}' to close a unicode escape.".to_string()));
                 }
-            }
-            "float32" => {
-                if radix != 10 {
-                    return Err(LexicalError::new(self.get_pos(), "Cannot use radix prefix in float type.".to_string()));
+                let code = if digits.len() <= 6 { u32::from_str_radix(&digits, 16).ok() } else { None };
+                match code.and_then(char::from_u32) {
+                    Some(ch) => ch,
+                    None => {
+                        let msg = format!("Invalid unicode escape '\\u{{{}}}'.", digits);
+                        return Err(LexicalError::new(self.get_pos_from(line, col), msg));
+                    }
                 }
-                let number: f32 = number_str.parse().or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid float32 number: {}", err))))?;
-                TokenType::Literal(TokenLiteral::Float(TokenFloat::Float32(number)))
             }
-            "float64" => {
-                if radix != 10 {
-                    return Err(LexicalError::new(self.get_pos(), "Cannot use radix prefix in float type.".to_string()));
-                }
-                let number: f64 = number_str.parse().or_else(|err| Err(LexicalError::new(self.get_pos(), format!("Invalid float32 number: {}", err))))?;
-                TokenType::Literal(TokenLiteral::Float(TokenFloat::Float64(number)))
+            ch => {
+                let msg = format!("Unknown escape sequence '\\{}'.", ch.escape_default());
+                return Err(LexicalError::new(self.get_pos_from(line, col), msg));
             }
-            _ => return Err(LexicalError::new(self.get_pos(), format!("Invalid number tag: {}", tag_str))),
+        };
+        Ok(ch)
+    }
+
+    fn scan_identifier(&mut self, _ctx: &LexerContext) -> Token {
+        let mut name = String::from(self.last());
+        self.scan_identifier_rest(&mut name);
+
+        let token_type = match self.keywords.get(name.as_str()) {
+            Some(keyword) => TokenType::Keyword(*keyword),
+            None => TokenType::Identifier(self.symbols.intern(&name)),
         };
 
-        Ok(self.make_token(token_type))
+        self.make_token(token_type)
     }
 
-    fn make_token(&self, token_type: TokenType) -> Token {
-        Token::new(self.get_pos(), token_type)
+    fn scan_label(&mut self, _ctx: &LexerContext) -> LexicalResult<Token> {
+        let ch = self.peek_next();
+        if ch != '_' && !unicode_ident::is_xid_start(ch) {
+            return Err(LexicalError::new(self.get_current_pos(), "Expect a label name after '\''.".to_string()));
+        }
+
+        let mut name = String::new();
+        self.scan_identifier_rest(&mut name);
+        if self.match_advance('\'') {
+            return Err(LexicalError::new(self.get_pos(), "A character literal must hold exactly one character.".to_string()));
+        }
+        let symbol = self.symbols.intern(&name);
+        Ok(self.make_token(TokenType::Label(symbol)))
     }
 
-    fn skip_whitespace(&mut self) {
+    fn scan_identifier_rest(&mut self, name: &mut String) {
         loop {
             let ch = self.peek_next();
-            if ch == '\0' || !ch.is_whitespace() {
+            if ch == '\0' || !unicode_ident::is_xid_continue(ch) {
                 break;
             }
+            name.push(ch);
             self.advance();
         }
     }
 
+    fn make_token(&self, token_type: TokenType) -> Token {
+        Token::new(self.get_pos(), token_type)
+    }
+
+    /// Skip whitespace, `//` line comments and nestable `/* */` block comments.
+    ///
+    /// `///` doc comments are left to be scanned as tokens, while `////` and longer are plain comments.
+    fn skip_whitespace(&mut self) -> LexicalResult<()> {
+        loop {
+            match (self.peek_next(), self.peek_at(1)) {
+                ('/', '/') if self.peek_at(2) == '/' && self.peek_at(3) != '/' => return Ok(()),
+                ('/', '/') => {
+                    while !matches!(self.peek_next(), '\0' | '\n') {
+                        self.advance();
+                    }
+                }
+                ('/', '*') => self.skip_block_comment()?,
+                (ch, _) if ch != '\0' && ch.is_whitespace() => {
+                    self.advance();
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> LexicalResult<()> {
+        let start_pos = Position {
+            start_line: self.line,
+            start_col: self.current_col,
+            end_line: self.line,
+            end_col: self.current_col + 1,
+        };
+        self.advance();
+        self.advance();
+
+        let mut depth = 1;
+        while depth > 0 {
+            match (self.peek_next(), self.peek_at(1)) {
+                ('\0', _) => return Err(LexicalError::new(start_pos, "Unterminated block comment.".to_string())),
+                ('/', '*') => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                ('*', '/') => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+        Ok(())
+    }
+
     #[must_use]
     fn peek_next(&self) -> char {
         if self.current < self.chars.len() {
@@ -293,6 +638,12 @@ impl Lexer {
         }
     }
 
+    /// Peek the character `offset` places after the next one.
+    #[must_use]
+    fn peek_at(&self, offset: usize) -> char {
+        self.chars.get(self.current + offset).copied().unwrap_or('\0')
+    }
+
     #[must_use]
     fn last(&self) -> char {
         if self.current != 0 {
@@ -337,7 +688,7 @@ impl Lexer {
             }
         } else {
             Position {
-                start_line: self.line,
+                start_line: self.start_line,
                 start_col: self.start_col,
                 end_line: self.line,
                 end_col: self.current_col - 1,
@@ -363,14 +714,27 @@ impl Lexer {
         }
     }
 
+    /// The position from `line` and `col` to the last consumed character, for errors inside a token.
+    fn get_pos_from(&self, line: usize, col: usize) -> Position {
+        Position {
+            start_line: line,
+            start_col: col,
+            end_line: self.line,
+            end_col: self.current_col.max(2) - 1,
+        }
+    }
+
     fn set_col(&mut self) {
+        self.start_line = self.line;
         self.start_col = self.current_col;
     }
 
+    /// Skip the rest of an invalid lexeme, stopping before whitespace or a delimiter that may end a statement
+    /// or an expression so that the parser can resume from there.
     fn synchronize(&mut self) {
         loop {
             let ch = self.peek_next();
-            if ch == '\0' || ch.is_whitespace() {
+            if ch == '\0' || ch.is_whitespace() || matches!(ch, ';' | ',' | '(' | ')' | '{' | '}') {
                 break;
             }
             self.advance();
@@ -378,3 +742,195 @@ impl Lexer {
         self.set_col();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn try_lex(source: &str) -> LexicalResult<Vec<TokenType>> {
+        let ctx = LexerContext { debug_print_token: false };
+        let mut lexer = Lexer::new(source, SymbolInterner::create());
+        let mut tokens = Vec::new();
+        loop {
+            let token = lexer.get_token(&ctx)?;
+            if token.is_eof() {
+                return Ok(tokens);
+            }
+            tokens.push(token.token_type);
+        }
+    }
+
+    fn lex(source: &str) -> Vec<TokenType> {
+        match try_lex(source) {
+            Ok(tokens) => tokens,
+            Err(err) => panic!("unexpected lexical error: {}", err.msg),
+        }
+    }
+
+    fn lex_error(source: &str) -> LexicalError {
+        match try_lex(source) {
+            Ok(tokens) => panic!("expected a lexical error, got {:?}", tokens),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn same_identifier_same_symbol() {
+        let tokens = lex("foo bar foo");
+        let [TokenType::Identifier(foo), TokenType::Identifier(bar), TokenType::Identifier(foo_again)] = tokens[..] else {
+            panic!("expected three identifiers, got {:?}", tokens);
+        };
+        assert_eq!(foo, foo_again);
+        assert_ne!(foo, bar);
+    }
+
+    #[test]
+    fn keywords_are_not_identifiers() {
+        let tokens = lex("let mut letter");
+        assert!(matches!(tokens[..], [
+            TokenType::Keyword(TokenKeyword::Let),
+            TokenType::Keyword(TokenKeyword::Mut),
+            TokenType::Identifier(_),
+        ]));
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        let tokens = lex("_x 变量 x1");
+        assert!(matches!(tokens[..], [TokenType::Identifier(_), TokenType::Identifier(_), TokenType::Identifier(_)]));
+    }
+
+    #[test]
+    fn line_comments_are_skipped() {
+        let tokens = lex("1 // one\n2 //// not a doc comment\n");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(1))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(2))),
+        ]));
+    }
+
+    #[test]
+    fn nested_block_comments_are_skipped() {
+        let tokens = lex("1 /* a /* b */ still a comment */ 2");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(1))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(2))),
+        ]));
+    }
+
+    #[test]
+    fn doc_comment_is_a_token() {
+        let tokens = lex("/// Adds one.  \nfn");
+        assert!(matches!(&tokens[..], [TokenType::DocComment(text), TokenType::Keyword(_)] if text == " Adds one."));
+    }
+
+    #[test]
+    fn unterminated_block_comment() {
+        let err = lex_error("1\n  /* a /* b */");
+        assert_eq!(err.msg, "Unterminated block comment.");
+        assert_eq!((err.pos.start_line, err.pos.start_col), (2, 3));
+    }
+
+    #[test]
+    fn string_escapes_are_resolved() {
+        let tokens = lex(r#""a\n\t\r\0\\\"\'\u{48}\u{1F600}""#);
+        assert!(matches!(&tokens[..], [TokenType::Literal(TokenLiteral::Str(s))] if s == "a\n\t\r\0\\\"'H\u{1F600}"));
+    }
+
+    #[test]
+    fn raw_strings_keep_escapes() {
+        let tokens = lex(r###"r"C:\path" r#"say "hi""#"###);
+        assert!(matches!(&tokens[..], [
+            TokenType::Literal(TokenLiteral::Str(a)),
+            TokenType::Literal(TokenLiteral::Str(b)),
+        ] if a == r"C:\path" && b == r#"say "hi""#));
+    }
+
+    #[test]
+    fn char_escapes() {
+        let tokens = lex(r"'\n' '\'' '\u{e9}' 'x'");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Char('\n')),
+            TokenType::Literal(TokenLiteral::Char('\'')),
+            TokenType::Literal(TokenLiteral::Char('é')),
+            TokenType::Literal(TokenLiteral::Char('x')),
+        ]));
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        let err = lex_error(r#""ab\q""#);
+        assert_eq!(err.msg, "Unknown escape sequence '\\q'.");
+        assert_eq!((err.pos.start_col, err.pos.end_col), (4, 5));
+        assert_eq!(lex_error(r#""\u{D800}""#).msg, "Invalid unicode escape '\\u{D800}'.");
+        assert_eq!(lex_error(r#""\u{41""#).msg, "Expect '}' to close a unicode escape.");
+        assert_eq!(lex_error(r#""\u41""#).msg, "Expect '{' after '\\u'.");
+    }
+
+    #[test]
+    fn unterminated_strings() {
+        assert_eq!(lex_error("\"abc").msg, "Unterminated string literal.");
+        assert_eq!(lex_error("r#\"abc\"").msg, "Unterminated raw string literal.");
+        assert_eq!(lex_error("''").msg, "Empty character literal.");
+    }
+
+    #[test]
+    fn integer_radixes_and_separators() {
+        let tokens = lex("0b1010 0o17 0xFF 1_000_000 0x_7f_i8");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(10))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(15))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(255))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Untyped(1_000_000))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int8(127))),
+        ]));
+    }
+
+    #[test]
+    fn integer_tags() {
+        let tokens = lex("255u8 255uint8 1i128 340282366920938463463374607431768211455u128");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt8(255))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt8(255))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::Int128(1))),
+            TokenType::Literal(TokenLiteral::Integer(TokenInteger::UInt128(u128::MAX))),
+        ]));
+        assert_eq!(lex_error("256u8").msg, "Invalid uint8 number: number too large to fit in target type");
+        assert_eq!(lex_error("1u7").msg, "Invalid number tag: u7");
+        assert_eq!(lex_error("0b102").msg, "Invalid digit '2' in a base 2 number.");
+    }
+
+    #[test]
+    fn decimal_floats_and_exponents() {
+        let tokens = lex("1.5 .5 2. 1e3 2.5E-2 1f32 3f64");
+        let values: Vec<f64> = tokens.iter().map(|token| match token {
+            TokenType::Literal(TokenLiteral::Float(TokenFloat::Untyped(value) | TokenFloat::Float64(value))) => *value,
+            TokenType::Literal(TokenLiteral::Float(TokenFloat::Float32(value))) => *value as f64,
+            token => panic!("expected a float, got {:?}", token),
+        }).collect();
+        assert_eq!(values, [1.5, 0.5, 2.0, 1000.0, 0.025, 1.0, 3.0]);
+        assert!(matches!(tokens[5], TokenType::Literal(TokenLiteral::Float(TokenFloat::Float32(_)))));
+        assert!(matches!(tokens[6], TokenType::Literal(TokenLiteral::Float(TokenFloat::Float64(_)))));
+    }
+
+    #[test]
+    fn hexadecimal_floats() {
+        let tokens = lex("0x1.8p3 0x1p-2 0xAp0f32");
+        assert!(matches!(tokens[..], [
+            TokenType::Literal(TokenLiteral::Float(TokenFloat::Untyped(12.0))),
+            TokenType::Literal(TokenLiteral::Float(TokenFloat::Untyped(0.25))),
+            TokenType::Literal(TokenLiteral::Float(TokenFloat::Float32(10.0))),
+        ]));
+        assert_eq!(lex_error("0x1.8").msg, "A hexadecimal float needs a 'p' exponent.");
+        assert_eq!(lex_error("0b1.0").msg, "Cannot use radix prefix in float type.");
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        assert_eq!(lex_error("1e").msg, "Expect digits in the exponent.");
+        assert_eq!(lex_error("1e+f32").msg, "Expect digits in the exponent.");
+        assert_eq!(lex_error("1.2.3").msg, "Unexpected dot.");
+        assert_eq!(lex_error("1.5i32").msg, "Invalid int32 number: Unexpected dot.");
+        assert_eq!(lex_error("1e3u8").msg, "Invalid uint8 number: Unexpected exponent.");
+    }
+}
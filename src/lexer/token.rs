@@ -4,6 +4,7 @@
 
 use std::fmt::Display;
 use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
 
 pub struct Token {
     pub pos: Position,
@@ -21,7 +22,7 @@ impl Token {
 
     #[must_use]
     pub fn is_eof(&self) -> bool {
-        matches!(self.token_type, TokenType::EOF)
+        matches!(self.token_type, TokenType::Eof)
     }
 }
 
@@ -34,16 +35,25 @@ impl Display for Token {
 #[derive(Debug)]
 pub enum TokenType {
     Literal(TokenLiteral),
-    Identifier(String),
+    Identifier(Symbol),
+    Keyword(TokenKeyword),
+    Label(Symbol),
     Operator(TokenOperator),
     Paren(TokenParen),
-    EOF,
+    Punctuation(TokenPunctuation),
+    Assign(TokenAssign),
+    /// The text of a `///` doc comment after the slashes, to be attached to the declaration that follows.
+    DocComment(String),
+    Eof,
 }
 
 #[derive(Debug)]
 pub enum TokenLiteral {
     Integer(TokenInteger),
     Float(TokenFloat),
+    /// A string literal with its escape sequences already resolved.
+    Str(String),
+    Char(char),
 }
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
@@ -52,13 +62,73 @@ pub enum TokenOperator {
     Minus,
     Multiply,
     Divide,
+    Remainder,
     Power,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Not,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKeyword {
+    Let,
+    Mut,
+    Fn,
+    If,
+    Else,
+    While,
+    Loop,
+    Break,
+    Continue,
+    Return,
+    True,
+    False,
+    As,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPunctuation {
+    Semicolon,
+    Colon,
+    Comma,
+    Arrow,
+}
+
+/// A plain assignment `=` or a compound assignment such as `+=`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAssign {
+    Assign,
+    PlusAssign,
+    MinusAssign,
+    MultiplyAssign,
+    DivideAssign,
+    RemainderAssign,
+    PowerAssign,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+    ShiftLeftAssign,
+    ShiftRightAssign,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenParen {
     LeftParen,
     RightParen,
+    LeftBrace,
+    RightBrace,
 }
 
 #[derive(Debug)]
@@ -73,10 +143,14 @@ pub enum TokenInteger {
     UInt64(u64),
     Int128(i128),
     UInt128(u128),
+    /// An integer without a type tag, whose type is decided by the type checker.
+    Untyped(u128),
 }
 
 #[derive(Debug)]
 pub enum TokenFloat {
     Float32(f32),
     Float64(f64),
+    /// A float without a type tag, whose type is decided by the type checker.
+    Untyped(f64),
 }
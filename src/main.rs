@@ -2,12 +2,18 @@
 
 #![cfg_attr(debug_assertions, allow(dead_code))]
 
-use crate::args::{ArgContext, OptionArg};
+use crate::args::{ArgContext, EmitKind, OptionArg};
 use crate::debug::ast_printer::AstPrinter;
 use crate::main_error::{print_error, CopySource};
 use crate::parser::Parser;
 use std::io::Read;
+use crate::analyzer::Analyzer;
+use crate::analyzer::borrowck::BorrowChecker;
 use crate::compiler::Compiler;
+use crate::compiler_data::data_type::TypeInterner;
+use crate::compiler_data::symbol::SymbolInterner;
+use crate::mir::lower::MirBuilder;
+use crate::mir::printer::MirPrinter;
 
 mod lexer;
 mod compiler_data;
@@ -16,7 +22,10 @@ mod ast;
 mod debug;
 mod args;
 mod parser;
+mod analyzer;
 mod compiler;
+mod mir;
+mod repl;
 
 fn main() {
     println!("Welcome to use Iranta!");
@@ -33,59 +42,55 @@ fn main() {
     let arg_context = ArgContext::parse_args(&args).unwrap_or_else(|err| { eprintln!("Error: {}", err); std::process::exit(1); });
 
     for option in &arg_context.option_args {
-        match option {
-            OptionArg::Version => {
-                println!(
-                    "Iranta 1.0.0-alpha on {} {} [Kernel: {} | Host: {}]",
-                    sysinfo::System::name().unwrap_or("UNKNOWN".to_string()),
-                    sysinfo::System::os_version().unwrap_or("UNKNOWN".to_string()),
-                    sysinfo::System::kernel_version().unwrap_or("UNKNOWN".to_string()),
-                    sysinfo::System::host_name().unwrap_or("UNKNOWN".to_string()),
-                );
-            }
-            _ => (),
+        if let OptionArg::Version = option {
+            println!(
+                "Iranta 1.0.0-alpha on {} {} [Kernel: {} | Host: {}]",
+                sysinfo::System::name().unwrap_or("UNKNOWN".to_string()),
+                sysinfo::System::os_version().unwrap_or("UNKNOWN".to_string()),
+                sysinfo::System::kernel_version().unwrap_or("UNKNOWN".to_string()),
+                sysinfo::System::host_name().unwrap_or("UNKNOWN".to_string()),
+            );
         }
     }
 
-    if let Some(file_name) = &arg_context.file_name {
-        if let Err(()) = compile(file_name, &arg_context) {
-            std::process::exit(1);
-        }
+    if arg_context.is_repl() {
+        repl::run_repl(&arg_context);
+        return;
+    }
+
+    if let Some(file_name) = &arg_context.file_name && let Err(()) = compile(file_name, &arg_context) {
+        std::process::exit(1);
     }
 }
 
 fn compile(source_path: &str, arg_context: &ArgContext) -> Result<(), ()> {
     println!("Compiling...");
 
-    let mut file = std::fs::File::open(source_path).or_else(|err| {
+    let mut file = std::fs::File::open(source_path).map_err(|err| {
         eprintln!("Cannot open file \"{}\": {}", source_path, err);
-        Err(())
     })?;
 
     let mut source = String::new();
-    file.read_to_string(&mut source).or_else(|err| {
+    file.read_to_string(&mut source).map_err(|err| {
         eprintln!("Cannot read file \"{}\": {}", source_path, err);
-        Err(())
     })?;
 
     let copy_source = CopySource {
         lines: source.split("\n").collect(),
+        hidden_lines: 0,
     };
 
-    let mut parser = Parser::new(&source, arg_context);
-    let expr = parser.parse().or_else(|error| {
-        let cnt = error.list.len();
-        for err in error.list.into_iter() {
-            print_error("Syntax Error", err, &copy_source);
-        }
-        println!("{} errors in total.", cnt);
-        Err(())
-    })?;
+    let mut parser = Parser::new(&source, arg_context, SymbolInterner::create());
+    let (mut program, syntax_errors) = parser.parse();
+    let mut error_cnt = syntax_errors.list.len();
+    for err in syntax_errors.list.into_iter() {
+        print_error("Syntax Error", err, &copy_source);
+    }
 
     for option in &arg_context.option_args {
         if let OptionArg::DebugPrintAST = option {
-            let ast_printer = AstPrinter::new();
-            println!("{}", ast_printer.print_expr(expr.as_ref()));
+            let ast_printer = AstPrinter::new(parser.get_symbols());
+            println!("{}", ast_printer.print_program(&program));
             break;
         }
     }
@@ -97,15 +102,53 @@ fn compile(source_path: &str, arg_context: &ArgContext) -> Result<(), ()> {
         }
     }
 
-    let llvm_context = inkwell::context::Context::create();
-    let compiler = Compiler::new(expr, output_file.to_str().unwrap_or("IRANTA_DEFAULT").to_string(), &llvm_context);
-    compiler.compile(&arg_context).or_else(|error| {
-        let cnt = error.list.len();
+    let mut analyzer = Analyzer::new(parser.into_symbols(), TypeInterner::create());
+    if let Err(error) = analyzer.analyze(&mut program, error_cnt > 0) {
+        error_cnt += error.list.len();
         for err in error.list.into_iter() {
             print_error("Compile Error", err, &copy_source);
         }
-        println!("{} errors in total.", cnt);
-        Err(())
+    }
+    // The analysis goes on after syntax errors to report more errors, but nothing is compiled from a broken program.
+    if error_cnt > 0 {
+        println!("{} errors in total.", error_cnt);
+        return Err(());
+    }
+    let (symbols, type_interner) = analyzer.into_parts();
+    let mir = MirBuilder::new(&type_interner).build(&program);
+    let borrow_errors = BorrowChecker::new(&symbols, &type_interner).check(&mir, None);
+    if !borrow_errors.is_empty() {
+        let error_cnt = borrow_errors.len();
+        for err in borrow_errors.into_iter() {
+            print_error("Compile Error", err, &copy_source);
+        }
+        println!("{} errors in total.", error_cnt);
+        return Err(());
+    }
+
+    if arg_context.emit_kind() == EmitKind::Mir && !arg_context.is_run() {
+        let mir_text = MirPrinter::new(&symbols, &type_interner).print_mir(&mir);
+        std::fs::write(output_file.with_extension("mir"), mir_text).map_err(|err| {
+            eprintln!("Error: Cannot write to output file: {}", err);
+        })?;
+        println!("Finished Compiling Successfully!");
+        return Ok(());
+    }
+
+    let llvm_context = inkwell::context::Context::create();
+    let mut compiler = Compiler::new(symbols, type_interner, output_file.to_str().unwrap_or("IRANTA_DEFAULT").to_string(), &llvm_context);
+    compiler.compile(&mir, arg_context);
+
+    if arg_context.is_run() {
+        println!("Finished Compiling Successfully! Running...");
+        let code = compiler.run_jit(arg_context).map_err(|err| {
+            eprintln!("Error: {}", err);
+        })?;
+        std::process::exit(code);
+    }
+
+    compiler.write_output(arg_context).map_err(|err| {
+        eprintln!("Error: {}", err);
     })?;
 
     println!("Finished Compiling Successfully!");
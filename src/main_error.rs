@@ -7,18 +7,47 @@ pub trait IrantaCompilerError {
     fn get_pos(&self) -> &Position;
     #[must_use]
     fn get_msg(&self) -> &str;
+    /// Other places in the source related to the error, such as where a used value was moved.
+    #[must_use]
+    fn get_notes(&self) -> Vec<(&Position, &str)> {
+        vec![]
+    }
 }
 
 pub struct CopySource<'a> {
     pub lines: Vec<&'a str>,
+    /// The number of lines of the earlier inputs of the REPL, before the newest one. Lines are counted from after them.
+    pub hidden_lines: usize,
 }
 
 pub fn print_error(error_type: &str, error: impl IrantaCompilerError, copy_source: &CopySource) {
     let pos = error.get_pos();
     let msg = error.get_msg();
 
-    println!("{} {} {}", error_type, pos, msg);
+    println!("{} {} {}", error_type, show_pos(pos, copy_source), msg);
+    print_source(pos, copy_source);
+
+    for (pos, msg) in error.get_notes() {
+        println!("Note {} {}", show_pos(pos, copy_source), msg);
+        print_source(pos, copy_source);
+    }
+}
+
+/// Write `pos` with its lines counted from after the hidden lines.
+fn show_pos(pos: &Position, copy_source: &CopySource) -> String {
+    if pos.start_line <= copy_source.hidden_lines {
+        return "[earlier input]".to_string();
+    }
+    let pos = Position {
+        start_line: pos.start_line - copy_source.hidden_lines,
+        end_line: pos.end_line - copy_source.hidden_lines,
+        ..pos.clone()
+    };
+    pos.to_string()
+}
 
+/// Print the lines of the source at `pos`, marking the part it covers.
+fn print_source(pos: &Position, copy_source: &CopySource) {
     let line = copy_source.lines[pos.start_line - 1];
     println!("> {}", line);
     print!("  ");
@@ -0,0 +1,569 @@
+//! The module of `MirBuilder`, which lowers a checked program to MIR.
+
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::expr::{Expr, ExprBinary, ExprIf, ExprLiteral, ExprType, ExprUnary};
+use crate::ast::program::Program;
+use crate::ast::stmt::{Stmt, StmtAssign, StmtFn, StmtType};
+use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
+use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
+use crate::mir::{BlockData, BlockId, Body, BodyKind, Constant, Local, LocalDecl, Mir, Operand, Place, Rvalue, Statement, StatementKind, Storage, Terminator};
+
+pub struct MirBuilder<'a> {
+    type_interner: &'a TypeInterner,
+    locals: Vec<LocalDecl>,
+    blocks: Vec<PartialBlock>,
+    current: BlockId,
+    /// The variables of each scope in order of declaration, including the shadowed ones.
+    scopes: Vec<Vec<(Symbol, Local)>>,
+    loops: Vec<LoopScope>,
+    /// The local receiving the values of type `void` or `never`, which hold nothing.
+    void_local: Option<Local>,
+    /// Whether the program is a REPL input, whose top-level variables are kept in globals.
+    repl: bool,
+    /// The top-level variables of the earlier REPL inputs.
+    globals: Vec<(Symbol, TypeId)>,
+}
+
+/// A block being built, whose terminator is not known until control leaves it.
+#[derive(Default)]
+struct PartialBlock {
+    stmts: Vec<Statement>,
+    terminator: Option<Terminator>,
+}
+
+/// The jump targets of a loop being lowered.
+struct LoopScope {
+    label: Option<Symbol>,
+    break_block: BlockId,
+    continue_block: BlockId,
+    /// The place receiving the values of `break` statements, for a `loop`.
+    dest: Option<Place>,
+    /// The number of scopes outside the loop, whose variables stay alive when jumping out of it.
+    scope_depth: usize,
+}
+
+impl<'a> MirBuilder<'a> {
+    #[must_use]
+    pub fn new(type_interner: &'a TypeInterner) -> Self {
+        Self {
+            type_interner,
+            locals: vec![],
+            blocks: vec![],
+            current: BlockId(0),
+            scopes: vec![],
+            loops: vec![],
+            void_local: None,
+            repl: false,
+            globals: vec![],
+        }
+    }
+
+    /// Lower the program as a REPL input, keeping its top-level variables in globals.
+    pub fn set_repl(&mut self) {
+        self.repl = true;
+    }
+
+    /// Declare a top-level variable of an earlier REPL input.
+    pub fn declare_global(&mut self, name: Symbol, type_id: TypeId) {
+        self.globals.push((name, type_id));
+    }
+
+    /// Lower a program that passed the analyzer, the top level first and then each function.
+    #[must_use]
+    pub fn build(mut self, program: &Program) -> Mir {
+        let mut bodies = vec![];
+
+        self.start_body(program.tail.as_ref().map_or(TypeId::VOID, |tail| tail.type_id));
+        for (name, type_id) in std::mem::take(&mut self.globals) {
+            let local = self.new_local(type_id, Some(name));
+            self.locals[local.0].storage = Storage::ExternGlobal;
+            self.scopes[0].push((name, local));
+        }
+        for stmt in &program.stmts {
+            if !matches!(stmt.stmt_type, StmtType::Fn(_)) {
+                self.lower_statement(stmt);
+            }
+        }
+        if let Some(tail) = &program.tail {
+            self.lower_into(tail, Place::local(Local(0)));
+        }
+        if let Some(last) = program.tail.as_ref().map(|tail| &tail.pos).or(program.stmts.last().map(|stmt| &stmt.pos)) {
+            self.kill_scopes(0, &Self::end_of(last));
+        }
+        self.terminate(Terminator::Return);
+        bodies.push(self.finish_body(BodyKind::TopLevel, 0));
+
+        for stmt in &program.stmts {
+            if let StmtType::Fn(stmt_fn) = &stmt.stmt_type {
+                bodies.push(self.lower_function(stmt_fn));
+            }
+        }
+        Mir { bodies }
+    }
+
+    fn lower_function(&mut self, stmt_fn: &StmtFn) -> Body {
+        self.start_body(stmt_fn.return_type.as_ref().map_or(TypeId::VOID, |return_type| return_type.type_id));
+        for param in &stmt_fn.params {
+            let local = self.new_local(param.param_type.type_id, Some(param.name));
+            self.scopes.last_mut().unwrap().push((param.name, local));
+        }
+        self.lower_into(&stmt_fn.body, Place::local(Local(0)));
+        self.kill_scopes(0, &Self::end_of(&stmt_fn.body.pos));
+        self.terminate(Terminator::Return);
+        self.finish_body(BodyKind::Fn(stmt_fn.name), stmt_fn.params.len())
+    }
+
+    fn start_body(&mut self, return_type: TypeId) {
+        self.locals = vec![LocalDecl { type_id: return_type, name: None, storage: Storage::Stack }];
+        self.blocks = vec![PartialBlock::default()];
+        self.current = BlockId(0);
+        self.scopes = vec![vec![]];
+        self.loops.clear();
+        self.void_local = None;
+    }
+
+    /// Finish the body being built, dropping the blocks that cannot be reached from the entry.
+    fn finish_body(&mut self, kind: BodyKind, arg_cnt: usize) -> Body {
+        let blocks: Vec<BlockData> = std::mem::take(&mut self.blocks).into_iter().map(|block| BlockData {
+            stmts: block.stmts,
+            terminator: block.terminator.unwrap_or(Terminator::Unreachable),
+        }).collect();
+
+        let mut new_ids = vec![None; blocks.len()];
+        let mut order = vec![];
+        let mut worklist = vec![BlockId(0)];
+        while let Some(block_id) = worklist.pop() {
+            if new_ids[block_id.0].is_some() {
+                continue;
+            }
+            new_ids[block_id.0] = Some(BlockId(order.len()));
+            order.push(block_id);
+            worklist.extend(blocks[block_id.0].terminator.successors().into_iter().rev());
+        }
+
+        let mut blocks: Vec<Option<BlockData>> = blocks.into_iter().map(Some).collect();
+        let blocks = order.into_iter().map(|block_id| {
+            let mut block = blocks[block_id.0].take().unwrap();
+            let renumber = |target: &mut BlockId| *target = new_ids[target.0].unwrap();
+            match &mut block.terminator {
+                Terminator::Goto(target) => renumber(target),
+                Terminator::SwitchBool { then_block, else_block, .. } => {
+                    renumber(then_block);
+                    renumber(else_block);
+                }
+                Terminator::Return | Terminator::Unreachable => (),
+            }
+            block
+        }).collect();
+
+        Body {
+            kind,
+            arg_cnt,
+            locals: std::mem::take(&mut self.locals),
+            blocks,
+        }
+    }
+
+    fn new_local(&mut self, type_id: TypeId, name: Option<Symbol>) -> Local {
+        self.locals.push(LocalDecl { type_id, name, storage: Storage::Stack });
+        Local(self.locals.len() - 1)
+    }
+
+    /// Create a temporary holding a value of `type_id`, shared by all the values of type `void` or `never`.
+    fn temp(&mut self, type_id: TypeId) -> Place {
+        let local = match type_id {
+            TypeId::VOID | TypeId::NEVER => match self.void_local {
+                Some(local) => local,
+                None => {
+                    let local = self.new_local(TypeId::VOID, None);
+                    self.void_local = Some(local);
+                    local
+                }
+            },
+            _ => self.new_local(type_id, None),
+        };
+        Place::local(local)
+    }
+
+    fn find_variable(&self, name: Symbol) -> Local {
+        self.scopes.iter().flatten().rev().find(|(found, _)| *found == name).unwrap().1
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        self.blocks.push(PartialBlock::default());
+        BlockId(self.blocks.len() - 1)
+    }
+
+    fn push(&mut self, kind: StatementKind, pos: &Position) {
+        self.blocks[self.current.0].stmts.push(Statement { kind, pos: pos.clone() });
+    }
+
+    fn push_assign(&mut self, place: Place, rvalue: Rvalue, pos: &Position) {
+        self.push(StatementKind::Assign(place, rvalue), pos);
+    }
+
+    /// End the scopes deeper than `scope_depth` at `pos`, where their variables go out of scope, innermost first.
+    fn kill_scopes(&mut self, scope_depth: usize, pos: &Position) {
+        let locals: Vec<Local> = self.scopes[scope_depth..].iter().flatten().rev()
+            .map(|(_, local)| *local)
+            .filter(|local| self.locals[local.0].storage == Storage::Stack)
+            .collect();
+        for local in locals {
+            self.push(StatementKind::StorageDead(local), pos);
+        }
+    }
+
+    /// The empty position at the end of `pos`.
+    fn end_of(pos: &Position) -> Position {
+        Position {
+            start_line: pos.end_line,
+            end_line: pos.end_line,
+            start_col: pos.end_col,
+            end_col: pos.end_col,
+        }
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        self.blocks[self.current.0].terminator = Some(terminator);
+    }
+
+    /// Continue in a new block without predecessors after a jump, which is dropped at the end of the body.
+    fn start_dead_block(&mut self) {
+        self.current = self.new_block();
+    }
+
+    fn find_loop(&self, label: Option<Symbol>) -> usize {
+        match label {
+            Some(label) => self.loops.iter().rposition(|loop_scope| loop_scope.label == Some(label)).unwrap(),
+            None => self.loops.len() - 1,
+        }
+    }
+
+    /// Use the value of a place, moving it out if its type is not copyable.
+    fn use_place(&self, place: Place, type_id: TypeId) -> Operand {
+        if self.type_interner.is_copy(type_id) {
+            Operand::Copy(place)
+        } else {
+            Operand::Move(place)
+        }
+    }
+
+    /// Whether evaluating an expression has no effect on variables, so that the operands before it can be read later.
+    fn is_pure(expr: &Expr) -> bool {
+        match &expr.expr_type {
+            ExprType::Literal(_) | ExprType::Variable(_) | ExprType::Borrow(_) => true,
+            ExprType::Unary(unary) => Self::is_pure(&unary.rhs),
+            ExprType::Binary(binary) => Self::is_pure(&binary.lhs) && Self::is_pure(&binary.rhs),
+            ExprType::Cast(cast) => Self::is_pure(&cast.expr),
+            ExprType::Deref(deref) => Self::is_pure(&deref.expr),
+            _ => false,
+        }
+    }
+
+    /// The constant of a literal, with the type chosen by the analyzer for an untyped one.
+    fn constant(value: &Value, type_id: TypeId, negative: bool) -> Constant {
+        let value = match value {
+            Value::Integer(ValueInteger::Untyped(int)) => {
+                // The magnitude of a negative literal such as the `-128` of `let x: int8 = -128` wraps, and negating it
+                // gives the right value.
+                let bits = if negative { int.wrapping_neg() } else { *int };
+                Value::Integer(match type_id {
+                    TypeId::INT8 => ValueInteger::Int8(bits as i8),
+                    TypeId::UINT8 => ValueInteger::UInt8(bits as u8),
+                    TypeId::INT16 => ValueInteger::Int16(bits as i16),
+                    TypeId::UINT16 => ValueInteger::UInt16(bits as u16),
+                    TypeId::INT32 => ValueInteger::Int32(bits as i32),
+                    TypeId::UINT32 => ValueInteger::UInt32(bits as u32),
+                    TypeId::INT64 => ValueInteger::Int64(bits as i64),
+                    TypeId::UINT64 => ValueInteger::UInt64(bits as u64),
+                    TypeId::INT128 => ValueInteger::Int128(bits as i128),
+                    _ => ValueInteger::UInt128(bits),
+                })
+            }
+            Value::Float(ValueFloat::Untyped(float)) => Value::Float(match type_id {
+                TypeId::FLOAT32 => ValueFloat::Float32(*float as f32),
+                _ => ValueFloat::Float64(*float),
+            }),
+            value => value.clone(),
+        };
+        Constant { value, type_id }
+    }
+
+    /// The constant of a literal or of a negative untyped integer literal, which is checked as a whole.
+    fn literal_constant(expr: &Expr) -> Option<Constant> {
+        match &expr.expr_type {
+            ExprType::Literal(literal) => Some(Self::constant(&literal.value, expr.type_id, false)),
+            ExprType::Unary(ExprUnary { ope: UnaryOperator::Minus, rhs }) => match &rhs.expr_type {
+                ExprType::Literal(literal @ ExprLiteral { value: Value::Integer(ValueInteger::Untyped(_)) }) => {
+                    Some(Self::constant(&literal.value, expr.type_id, true))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn lower_statement(&mut self, stmt: &Stmt) {
+        match &stmt.stmt_type {
+            StmtType::Let(stmt_let) => {
+                let local = self.new_local(stmt_let.init.type_id, Some(stmt_let.name));
+                if self.repl && self.scopes.len() == 1 {
+                    self.locals[local.0].storage = Storage::Global { mutable: stmt_let.mutable };
+                }
+                self.lower_into(&stmt_let.init, Place::local(local));
+                // The variable comes into scope after its initializer, which may use a variable it shadows.
+                self.scopes.last_mut().unwrap().push((stmt_let.name, local));
+            }
+            StmtType::Assign(assign) => self.lower_stmt_assign(&stmt.pos, assign),
+            StmtType::Expr(stmt_expr) => self.lower_unused(&stmt_expr.expr),
+            StmtType::Fn(_) => unreachable!("Nested functions are rejected by the analyzer."),
+            StmtType::Return(stmt_return) => {
+                if let Some(value) = &stmt_return.value {
+                    self.lower_into(value, Place::local(Local(0)));
+                }
+                self.kill_scopes(0, &stmt.pos);
+                self.terminate(Terminator::Return);
+                self.start_dead_block();
+            }
+            StmtType::Break(stmt_break) => {
+                let loop_index = self.find_loop(stmt_break.label);
+                if let Some(value) = &stmt_break.value {
+                    match self.loops[loop_index].dest {
+                        Some(dest) => self.lower_into(value, dest),
+                        None => self.lower_unused(value),
+                    }
+                }
+                self.kill_scopes(self.loops[loop_index].scope_depth, &stmt.pos);
+                self.terminate(Terminator::Goto(self.loops[loop_index].break_block));
+                self.start_dead_block();
+            }
+            StmtType::Continue(stmt_continue) => {
+                let loop_index = self.find_loop(stmt_continue.label);
+                self.kill_scopes(self.loops[loop_index].scope_depth, &stmt.pos);
+                self.terminate(Terminator::Goto(self.loops[loop_index].continue_block));
+                self.start_dead_block();
+            }
+        }
+    }
+
+    fn lower_stmt_assign(&mut self, pos: &Position, assign: &StmtAssign) {
+        let place = match &assign.target.expr_type {
+            ExprType::Variable(target) => Place::local(self.find_variable(target.name)),
+            ExprType::Deref(deref) => {
+                let place = self.lower_deref_place(&deref.expr);
+                if Self::is_pure(&assign.value) {
+                    place
+                } else {
+                    // The reference is read before the value, which could assign another one to its variable.
+                    let temp = self.new_local(deref.expr.type_id, None);
+                    self.push_assign(Place::local(temp), Rvalue::Use(Operand::Copy(Place::local(place.local))), &deref.expr.pos);
+                    Place { local: temp, deref: true }
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        let value = self.lower_operand(&assign.value, true);
+        let rvalue = match assign.ope {
+            Some(ope) => Rvalue::BinaryOp(ope, Operand::Copy(place), value),
+            None => Rvalue::Use(value),
+        };
+        self.push_assign(place, rvalue, pos);
+    }
+
+    /// Lower an expression to an operand, where `consumes` tells whether the context takes its value or only reads it.
+    fn lower_operand(&mut self, expr: &Expr, consumes: bool) -> Operand {
+        if let Some(constant) = Self::literal_constant(expr) {
+            return Operand::Constant(constant);
+        }
+        match &expr.expr_type {
+            ExprType::Variable(variable) => {
+                let place = Place::local(self.find_variable(variable.name));
+                if consumes { self.use_place(place, expr.type_id) } else { Operand::Copy(place) }
+            }
+            ExprType::Deref(deref) => Operand::Copy(self.lower_deref_place(&deref.expr)),
+            _ => {
+                let place = self.temp(expr.type_id);
+                self.lower_into(expr, place);
+                self.use_place(place, expr.type_id)
+            }
+        }
+    }
+
+    /// Lower an expression whose value is not used, keeping the read of a place it names.
+    fn lower_unused(&mut self, expr: &Expr) {
+        let operand = self.lower_operand(expr, false);
+        if let ExprType::Variable(_) | ExprType::Deref(_) = expr.expr_type && let Operand::Copy(place) = operand {
+            self.push(StatementKind::PlaceMention(place), &expr.pos);
+        }
+    }
+
+    /// Lower operands evaluated from left to right, keeping the earlier ones in temporaries if a later one has effects.
+    fn lower_operands(&mut self, exprs: &[&Expr], consumes: bool) -> Vec<Operand> {
+        let mut operands = vec![];
+        for (i, expr) in exprs.iter().enumerate() {
+            let operand = self.lower_operand(expr, consumes);
+            let operand = match operand {
+                Operand::Copy(_) | Operand::Move(_) if !exprs[i + 1..].iter().all(|later| Self::is_pure(later)) => {
+                    let temp = self.temp(expr.type_id);
+                    self.push_assign(temp, Rvalue::Use(operand), &expr.pos);
+                    self.use_place(temp, expr.type_id)
+                }
+                operand => operand,
+            };
+            operands.push(operand);
+        }
+        operands
+    }
+
+    /// The place a reference points to, given the expression of the reference.
+    fn lower_deref_place(&mut self, reference: &Expr) -> Place {
+        match self.lower_operand(reference, false) {
+            Operand::Copy(place) | Operand::Move(place) if !place.deref => Place { local: place.local, deref: true },
+            operand => {
+                let temp = self.new_local(reference.type_id, None);
+                self.push_assign(Place::local(temp), Rvalue::Use(operand), &reference.pos);
+                Place { local: temp, deref: true }
+            }
+        }
+    }
+
+    /// Lower an expression, storing its value into `dest`.
+    fn lower_into(&mut self, expr: &Expr, dest: Place) {
+        if let Some(constant) = Self::literal_constant(expr) {
+            self.push_assign(dest, Rvalue::Use(Operand::Constant(constant)), &expr.pos);
+            return;
+        }
+        match &expr.expr_type {
+            ExprType::Literal(_) | ExprType::Variable(_) | ExprType::Deref(_) => {
+                let operand = self.lower_operand(expr, true);
+                self.push_assign(dest, Rvalue::Use(operand), &expr.pos);
+            }
+            ExprType::Unary(unary) => {
+                let operand = self.lower_operand(&unary.rhs, false);
+                let rvalue = match unary.ope {
+                    UnaryOperator::Plus => Rvalue::Use(operand),
+                    ope => Rvalue::UnaryOp(ope, operand),
+                };
+                self.push_assign(dest, rvalue, &expr.pos);
+            }
+            ExprType::Binary(binary) => self.lower_expr_binary(&expr.pos, binary, dest),
+            ExprType::Block(block) => {
+                self.scopes.push(vec![]);
+                for stmt in &block.stmts {
+                    self.lower_statement(stmt);
+                }
+                // The value is stored before the variables of the block go out of scope.
+                if let Some(tail) = &block.tail {
+                    self.lower_into(tail, dest);
+                }
+                self.kill_scopes(self.scopes.len() - 1, &Self::end_of(&expr.pos));
+                self.scopes.pop();
+            }
+            ExprType::Call(call) => {
+                let ExprType::Variable(callee) = &call.callee.expr_type else { unreachable!() };
+                let args: Vec<&Expr> = call.args.iter().collect();
+                let args = self.lower_operands(&args, true);
+                self.push_assign(dest, Rvalue::Call(callee.name, args), &expr.pos);
+            }
+            ExprType::If(expr_if) => self.lower_expr_if(expr_if, dest),
+            ExprType::While(expr_while) => {
+                let (cond_block, body_block, exit_block) = (self.new_block(), self.new_block(), self.new_block());
+                self.terminate(Terminator::Goto(cond_block));
+
+                self.current = cond_block;
+                let cond = self.lower_operand(&expr_while.cond, false);
+                self.terminate(Terminator::SwitchBool { cond, pos: expr_while.cond.pos.clone(), then_block: body_block, else_block: exit_block });
+
+                self.current = body_block;
+                self.lower_loop_body(expr_while.label, &expr_while.body, exit_block, cond_block, None);
+                self.current = exit_block;
+            }
+            ExprType::Loop(expr_loop) => {
+                let (body_block, exit_block) = (self.new_block(), self.new_block());
+                self.terminate(Terminator::Goto(body_block));
+
+                self.current = body_block;
+                self.lower_loop_body(expr_loop.label, &expr_loop.body, exit_block, body_block, Some(dest));
+                self.current = exit_block;
+            }
+            ExprType::Cast(cast) => {
+                let operand = self.lower_operand(&cast.expr, false);
+                let rvalue = if cast.expr.type_id == expr.type_id { Rvalue::Use(operand) } else { Rvalue::Cast(operand, expr.type_id) };
+                self.push_assign(dest, rvalue, &expr.pos);
+            }
+            ExprType::Borrow(borrow) => {
+                let ExprType::Variable(variable) = &borrow.expr.expr_type else { unreachable!() };
+                let place = Place::local(self.find_variable(variable.name));
+                self.push_assign(dest, Rvalue::Ref { mutable: borrow.mutable, place }, &expr.pos);
+            }
+            ExprType::Error => unreachable!("Syntax errors stop the compilation before lowering."),
+        }
+    }
+
+    fn lower_expr_binary(&mut self, pos: &Position, binary: &ExprBinary, dest: Place) {
+        if let BinaryOperator::And | BinaryOperator::Or = binary.ope {
+            // Only evaluate the right operand when the left one does not decide the result.
+            let lhs = self.lower_operand(&binary.lhs, false);
+            let (rhs_block, short_block, merge_block) = (self.new_block(), self.new_block(), self.new_block());
+            let is_and = matches!(binary.ope, BinaryOperator::And);
+            let lhs_pos = binary.lhs.pos.clone();
+            self.terminate(if is_and {
+                Terminator::SwitchBool { cond: lhs, pos: lhs_pos, then_block: rhs_block, else_block: short_block }
+            } else {
+                Terminator::SwitchBool { cond: lhs, pos: lhs_pos, then_block: short_block, else_block: rhs_block }
+            });
+
+            self.current = short_block;
+            let short_circuit = Constant { value: Value::Bool(!is_and), type_id: TypeId::BOOL };
+            self.push_assign(dest, Rvalue::Use(Operand::Constant(short_circuit)), pos);
+            self.terminate(Terminator::Goto(merge_block));
+
+            self.current = rhs_block;
+            self.lower_into(&binary.rhs, dest);
+            self.terminate(Terminator::Goto(merge_block));
+
+            self.current = merge_block;
+            return;
+        }
+
+        let mut operands = self.lower_operands(&[&binary.lhs, &binary.rhs], false);
+        let (rhs, lhs) = (operands.pop().unwrap(), operands.pop().unwrap());
+        self.push_assign(dest, Rvalue::BinaryOp(binary.ope, lhs, rhs), pos);
+    }
+
+    fn lower_expr_if(&mut self, expr_if: &ExprIf, dest: Place) {
+        let cond = self.lower_operand(&expr_if.cond, false);
+        let (then_block, merge_block) = (self.new_block(), self.new_block());
+        let else_block = if expr_if.else_branch.is_some() { self.new_block() } else { merge_block };
+        self.terminate(Terminator::SwitchBool { cond, pos: expr_if.cond.pos.clone(), then_block, else_block });
+
+        self.current = then_block;
+        self.lower_into(&expr_if.then_branch, dest);
+        self.terminate(Terminator::Goto(merge_block));
+
+        if let Some(else_branch) = &expr_if.else_branch {
+            self.current = else_block;
+            self.lower_into(else_branch, dest);
+            self.terminate(Terminator::Goto(merge_block));
+        }
+
+        self.current = merge_block;
+    }
+
+    fn lower_loop_body(&mut self, label: Option<Symbol>, body: &Expr, break_block: BlockId, continue_block: BlockId, dest: Option<Place>) {
+        self.loops.push(LoopScope {
+            label,
+            break_block,
+            continue_block,
+            dest,
+            scope_depth: self.scopes.len(),
+        });
+        self.lower_operand(body, false);
+        self.loops.pop();
+        self.terminate(Terminator::Goto(continue_block));
+    }
+}
@@ -0,0 +1,202 @@
+//! The mid-level IR of Iranta, the control-flow graph of basic blocks that borrows are checked and code is generated on
+
+pub mod lower;
+pub mod printer;
+
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::Symbol;
+use crate::compiler_data::value::Value;
+use std::fmt::{Display, Formatter};
+
+/// The MIR of a whole program: the top level first, then its functions in order.
+pub struct Mir {
+    pub bodies: Vec<Body>,
+}
+
+/// A body, whose locals are `_0` holding the return value, then the parameters, then the variables and temporaries.
+pub struct Body {
+    pub kind: BodyKind,
+    /// The number of parameters, held by the locals `_1` to `_n`.
+    pub arg_cnt: usize,
+    pub locals: Vec<LocalDecl>,
+    /// The blocks of the body, where `bb0` is the entry.
+    pub blocks: Vec<BlockData>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BodyKind {
+    /// The statements at the top level, whose tail value is kept in `_0` to be shown.
+    TopLevel,
+    Fn(Symbol),
+}
+
+pub struct LocalDecl {
+    pub type_id: TypeId,
+    /// The name of the variable or parameter, `None` for a temporary.
+    pub name: Option<Symbol>,
+    pub storage: Storage,
+}
+
+/// Where the value of a local is kept.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    Stack,
+    /// A global holding a top-level variable of a REPL input, which later inputs link to.
+    Global {
+        mutable: bool,
+    },
+    /// The global of a top-level variable of an earlier REPL input.
+    ExternGlobal,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Local(pub usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub usize);
+
+pub struct BlockData {
+    pub stmts: Vec<Statement>,
+    pub terminator: Terminator,
+}
+
+/// A memory location: a local, or the value a reference held by a local points to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Place {
+    pub local: Local,
+    pub deref: bool,
+}
+
+pub struct Statement {
+    pub kind: StatementKind,
+    /// The position of the code the statement is lowered from, for the errors found in the MIR.
+    pub pos: Position,
+}
+
+pub enum StatementKind {
+    Assign(Place, Rvalue),
+    /// A variable goes out of scope, after which no reference to it may be used.
+    StorageDead(Local),
+    /// A place named by an expression whose value is not used, which generates no code but still reads the place.
+    PlaceMention(Place),
+}
+
+pub enum Terminator {
+    Goto(BlockId),
+    /// Go to `then_block` if `cond` is true, and to `else_block` otherwise. `pos` is the position of the condition.
+    SwitchBool {
+        cond: Operand,
+        pos: Position,
+        then_block: BlockId,
+        else_block: BlockId,
+    },
+    /// Leave the body with the value in `_0`.
+    Return,
+    Unreachable,
+}
+
+#[derive(Clone)]
+pub enum Operand {
+    Copy(Place),
+    /// Take the value of a place whose type is not copyable, leaving the place moved.
+    Move(Place),
+    Constant(Constant),
+}
+
+#[derive(Clone)]
+pub struct Constant {
+    pub value: Value,
+    pub type_id: TypeId,
+}
+
+pub enum Rvalue {
+    Use(Operand),
+    UnaryOp(UnaryOperator, Operand),
+    /// A binary operation other than `&&` and `||`, which are lowered to branches.
+    BinaryOp(BinaryOperator, Operand, Operand),
+    Cast(Operand, TypeId),
+    Ref {
+        mutable: bool,
+        place: Place,
+    },
+    Call(Symbol, Vec<Operand>),
+}
+
+impl Body {
+    #[must_use]
+    pub fn return_type(&self) -> TypeId {
+        self.locals[0].type_id
+    }
+
+    #[must_use]
+    pub fn place_type(&self, place: Place, type_interner: &TypeInterner) -> TypeId {
+        let type_id = self.locals[place.local.0].type_id;
+        if place.deref {
+            type_interner.ref_target(type_id).unwrap().0
+        } else {
+            type_id
+        }
+    }
+
+    #[must_use]
+    pub fn operand_type(&self, operand: &Operand, type_interner: &TypeInterner) -> TypeId {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.place_type(*place, type_interner),
+            Operand::Constant(constant) => constant.type_id,
+        }
+    }
+}
+
+impl Place {
+    #[must_use]
+    pub fn local(local: Local) -> Self {
+        Self { local, deref: false }
+    }
+}
+
+impl Terminator {
+    /// The blocks control may go to after this terminator.
+    #[must_use]
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Self::Goto(target) => vec![*target],
+            Self::SwitchBool { then_block, else_block, .. } => vec![*then_block, *else_block],
+            Self::Return | Self::Unreachable => vec![],
+        }
+    }
+}
+
+impl Display for Local {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "_{}", self.0)
+    }
+}
+
+impl Display for BlockId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bb{}", self.0)
+    }
+}
+
+impl Display for Place {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.deref {
+            write!(f, "(*{})", self.local)
+        } else {
+            write!(f, "{}", self.local)
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Copy(place) => write!(f, "copy {}", place),
+            Self::Move(place) => write!(f, "move {}", place),
+            Self::Constant(constant) => write!(f, "const {}", constant.value),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! The module of `MirPrinter`, which writes MIR as text for `--emit=mir`.
+
+use crate::ast::binary_ope::BinaryOperator;
+use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::{TypeId, TypeInterner};
+use crate::compiler_data::symbol::SymbolInterner;
+use crate::mir::{Body, BodyKind, Local, Mir, Rvalue, StatementKind, Terminator};
+use std::fmt::Write;
+
+pub struct MirPrinter<'a> {
+    symbols: &'a SymbolInterner,
+    type_interner: &'a TypeInterner,
+}
+
+impl<'a> MirPrinter<'a> {
+    #[must_use]
+    pub fn new(symbols: &'a SymbolInterner, type_interner: &'a TypeInterner) -> Self {
+        Self { symbols, type_interner }
+    }
+
+    #[must_use]
+    pub fn print_mir(&self, mir: &Mir) -> String {
+        let bodies: Vec<String> = mir.bodies.iter().map(|body| self.print_body(body)).collect();
+        bodies.join("\n")
+    }
+
+    fn type_name(&self, type_id: TypeId) -> String {
+        self.type_interner.to_data_type[&type_id].to_string()
+    }
+
+    #[must_use]
+    pub fn print_body(&self, body: &Body) -> String {
+        let mut out = String::new();
+        let name = match body.kind {
+            BodyKind::TopLevel => "<top level>",
+            BodyKind::Fn(name) => self.symbols.get_name(name),
+        };
+        let params: Vec<String> = (1..=body.arg_cnt).map(|i| format!("{}: {}", Local(i), self.type_name(body.locals[i].type_id))).collect();
+        writeln!(out, "fn {}({}) -> {} {{", name, params.join(", "), self.type_name(body.return_type())).unwrap();
+
+        for (i, local) in body.locals.iter().enumerate().skip(body.arg_cnt + 1) {
+            write!(out, "    let {}: {};", Local(i), self.type_name(local.type_id)).unwrap();
+            if let Some(name) = local.name {
+                write!(out, " // {}", self.symbols.get_name(name)).unwrap();
+            }
+            out.push('\n');
+        }
+
+        for (i, block) in body.blocks.iter().enumerate() {
+            if i > 0 || body.locals.len() > body.arg_cnt + 1 {
+                out.push('\n');
+            }
+            writeln!(out, "    bb{}: {{", i).unwrap();
+            for stmt in &block.stmts {
+                match &stmt.kind {
+                    StatementKind::Assign(place, rvalue) => writeln!(out, "        {} = {};", place, self.print_rvalue(rvalue)).unwrap(),
+                    StatementKind::StorageDead(local) => writeln!(out, "        StorageDead({});", local).unwrap(),
+                    StatementKind::PlaceMention(place) => writeln!(out, "        PlaceMention({});", place).unwrap(),
+                }
+            }
+            writeln!(out, "        {};", Self::print_terminator(&block.terminator)).unwrap();
+            out.push_str("    }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn print_rvalue(&self, rvalue: &Rvalue) -> String {
+        match rvalue {
+            Rvalue::Use(operand) => operand.to_string(),
+            Rvalue::UnaryOp(ope, operand) => format!("{}({})", Self::unary_name(*ope), operand),
+            Rvalue::BinaryOp(ope, lhs, rhs) => format!("{}({}, {})", Self::binary_name(*ope), lhs, rhs),
+            Rvalue::Cast(operand, type_id) => format!("{} as {}", operand, self.type_name(*type_id)),
+            Rvalue::Ref { mutable, place } => format!("&{}{}", if *mutable { "mut " } else { "" }, place),
+            Rvalue::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+                format!("{}({})", self.symbols.get_name(*name), args.join(", "))
+            }
+        }
+    }
+
+    fn print_terminator(terminator: &Terminator) -> String {
+        match terminator {
+            Terminator::Goto(target) => format!("goto -> {}", target),
+            Terminator::SwitchBool { cond, then_block, else_block, .. } => format!("switchBool({}) -> [true: {}, false: {}]", cond, then_block, else_block),
+            Terminator::Return => "return".to_string(),
+            Terminator::Unreachable => "unreachable".to_string(),
+        }
+    }
+
+    fn unary_name(ope: UnaryOperator) -> &'static str {
+        match ope {
+            UnaryOperator::Plus => unreachable!("Unary plus is lowered to a plain use."),
+            UnaryOperator::Minus => "Neg",
+            UnaryOperator::Not => "Not",
+            UnaryOperator::BitNot => "BitNot",
+        }
+    }
+
+    fn binary_name(ope: BinaryOperator) -> &'static str {
+        match ope {
+            BinaryOperator::Plus => "Add",
+            BinaryOperator::Minus => "Sub",
+            BinaryOperator::Multiply => "Mul",
+            BinaryOperator::Divide => "Div",
+            BinaryOperator::Remainder => "Rem",
+            BinaryOperator::Power => "Pow",
+            BinaryOperator::Equal => "Eq",
+            BinaryOperator::NotEqual => "Ne",
+            BinaryOperator::Less => "Lt",
+            BinaryOperator::LessEqual => "Le",
+            BinaryOperator::Greater => "Gt",
+            BinaryOperator::GreaterEqual => "Ge",
+            BinaryOperator::BitAnd => "BitAnd",
+            BinaryOperator::BitOr => "BitOr",
+            BinaryOperator::BitXor => "BitXor",
+            BinaryOperator::ShiftLeft => "Shl",
+            BinaryOperator::ShiftRight => "Shr",
+            BinaryOperator::And | BinaryOperator::Or => unreachable!("Logical operators are lowered to branches."),
+        }
+    }
+}
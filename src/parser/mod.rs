@@ -6,25 +6,34 @@ use std::collections::HashMap;
 use maplit::hashmap;
 use crate::args::{ArgContext, OptionArg};
 use crate::ast::binary_ope::BinaryOperator;
-use crate::ast::expr::{Expr, ExprBinary, ExprLiteral, ExprType, ExprUnary};
+use crate::ast::expr::{Expr, ExprBinary, ExprBlock, ExprBorrow, ExprCall, ExprCast, ExprDeref, ExprIf, ExprLiteral, ExprLoop, ExprType, ExprUnary, ExprVariable, ExprWhile};
+use crate::ast::program::Program;
+use crate::ast::stmt::{FnParam, Stmt, StmtAssign, StmtBreak, StmtContinue, StmtExpr, StmtFn, StmtLet, StmtReturn, StmtType};
+use crate::ast::type_expr::{TypeExpr, TypeExprType};
 use crate::ast::unary_ope::UnaryOperator;
+use crate::compiler_data::data_type::TypeId;
 use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
 use crate::compiler_data::value::{Value, ValueFloat, ValueInteger};
 use crate::lexer::context::LexerContext;
-use crate::lexer::token::{Token, TokenFloat, TokenInteger, TokenLiteral, TokenOperator, TokenParen, TokenType};
+use crate::lexer::token::{Token, TokenAssign, TokenFloat, TokenInteger, TokenKeyword, TokenLiteral, TokenOperator, TokenParen, TokenPunctuation, TokenType};
 use crate::lexer::Lexer;
-use crate::parser::error::{SyntaxError, SyntaxResultList};
+use crate::parser::error::{SyntaxError, SyntaxErrorList, SyntaxResultList};
 
 pub struct Parser<'a> {
     lexer_ctx: LexerContext,
     source: &'a String,
     lexer: Lexer,
-    precedence: HashMap<TokenOperator, (u32, u32)>
+    precedence: HashMap<TokenOperator, (u32, u32)>,
+    /// The errors recovered from so far, reported together once the whole file has been parsed.
+    errors: SyntaxErrorList,
+    /// Whether a missing '}' at the end of the file has been reported, so that nested blocks report it only once.
+    eof_reported: bool,
 }
 
 impl<'a> Parser<'a> {
     #[must_use]
-    pub fn new(source: &'a String, arg_ctx: &ArgContext) -> Self {
+    pub fn new(source: &'a String, arg_ctx: &ArgContext, symbols: SymbolInterner) -> Self {
         Self {
             lexer_ctx: LexerContext {
                 debug_print_token: {
@@ -39,11 +48,26 @@ impl<'a> Parser<'a> {
                 }
             },
             source,
-            lexer: Lexer::new(source),
+            lexer: Lexer::new(source, symbols),
             precedence: Self::init_precedence(),
+            errors: SyntaxErrorList::new(),
+            eof_reported: false,
         }
     }
 
+    /// The binding power of prefix operators, tighter than `as` but looser than `**`, so that `-a ** b` means `-(a ** b)`.
+    const PREFIX_BP: u32 = 82;
+
+    /// The binding power of postfix operations such as calls, tighter than every binary operator.
+    const POSTFIX_BP: u32 = 90;
+
+    /// The binding power of `as`, tighter than every binary operator but `**`, and looser than prefix operators and calls.
+    const CAST_BP: u32 = 80;
+
+    /// The binding power of the operand of a borrow or a dereference, tighter than `as` and every binary operator
+    /// but looser than calls, so that `*r * 2` multiplies the value `r` points to.
+    const REF_BP: u32 = 85;
+
     #[must_use]
     fn init_precedence() -> HashMap<TokenOperator, (u32, u32)> {
         hashmap! {
@@ -51,24 +75,462 @@ impl<'a> Parser<'a> {
             TokenOperator::Minus => (50, 51),
             TokenOperator::Multiply => (60, 61),
             TokenOperator::Divide => (60, 61),
-            TokenOperator::Power => (71, 70),
+            TokenOperator::Remainder => (60, 61),
+            TokenOperator::Power => (84, 83),
+            TokenOperator::ShiftLeft => (45, 46),
+            TokenOperator::ShiftRight => (45, 46),
+            TokenOperator::BitAnd => (40, 41),
+            TokenOperator::BitXor => (37, 38),
+            TokenOperator::BitOr => (34, 35),
+            TokenOperator::Or => (10, 11),
+            TokenOperator::And => (20, 21),
+            TokenOperator::Equal => (30, 31),
+            TokenOperator::NotEqual => (30, 31),
+            TokenOperator::Less => (30, 31),
+            TokenOperator::LessEqual => (30, 31),
+            TokenOperator::Greater => (30, 31),
+            TokenOperator::GreaterEqual => (30, 31),
+        }
+    }
+
+    /// Give back the symbol interner so that later stages can resolve identifier names.
+    #[must_use]
+    pub fn into_symbols(self) -> SymbolInterner {
+        self.lexer.into_symbols()
+    }
+
+    #[must_use]
+    pub fn get_symbols(&self) -> &SymbolInterner {
+        self.lexer.get_symbols()
+    }
+
+    /// Parse the whole file, recovering from syntax errors so that all of them are reported at once.
+    pub fn parse(&mut self) -> (Program, SyntaxErrorList) {
+        let (stmts, tail) = self.parse_stmt_list(|token_type| matches!(token_type, TokenType::Eof));
+        (Program { stmts, tail }, std::mem::replace(&mut self.errors, SyntaxErrorList::new()))
+    }
+
+    /// Parse statements until `is_end` accepts the next token, which is left in the lexer.
+    ///
+    /// An expression directly followed by the end token becomes the tail expression of the list.
+    /// A statement with a syntax error is recorded, skipped by `synchronize` and replaced by an error node.
+    fn parse_stmt_list(&mut self, is_end: fn(&TokenType) -> bool) -> (Vec<Stmt>, Option<Box<Expr>>) {
+        let mut stmts = vec![];
+
+        loop {
+            let token = match self.skip_doc_comments().and_then(|()| Ok(self.lexer.peek_token(&self.lexer_ctx)?)) {
+                Ok(token) => token,
+                Err(err) => {
+                    self.errors.combine(err);
+                    continue;
+                }
+            };
+            if is_end(&token.token_type) {
+                return (stmts, None);
+            }
+            let result = match &token.token_type {
+                TokenType::Eof => {
+                    if !self.eof_reported {
+                        self.eof_reported = true;
+                        let err = SyntaxError::new(token.pos.clone(), "Expect '}'.".to_string());
+                        self.errors.combine(err.into());
+                    }
+                    return (stmts, None);
+                }
+                TokenType::Keyword(TokenKeyword::Let) => self.parse_stmt_let(),
+                TokenType::Keyword(TokenKeyword::Fn) => self.parse_stmt_fn(),
+                TokenType::Keyword(TokenKeyword::Return) => self.parse_stmt_return(),
+                TokenType::Keyword(TokenKeyword::Break) => self.parse_stmt_break(),
+                TokenType::Keyword(TokenKeyword::Continue) => self.parse_stmt_continue(),
+                // A '}' closing no block, which is taken here as nothing else would take it.
+                TokenType::Paren(TokenParen::RightBrace) => match self.lexer.get_token(&self.lexer_ctx) {
+                    Ok(token) => Err(SyntaxError::new(token.pos, "Unexpected '}'.".to_string()).into()),
+                    Err(err) => Err(err.into()),
+                },
+                _ => match self.parse_stmt_expr(is_end) {
+                    Ok(Ok(stmt)) => Ok(stmt),
+                    Ok(Err(tail)) => return (stmts, Some(tail)),
+                    Err(err) => Err(err),
+                },
+            };
+            match result {
+                Ok(stmt) => stmts.push(stmt),
+                Err(err) => {
+                    let pos = self.recover(err);
+                    stmts.push(Stmt {
+                        pos: pos.clone(),
+                        stmt_type: StmtType::Expr(StmtExpr { expr: Self::error_expr(pos) }),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Parse a statement starting with an expression, or give back the expression if it is the tail of the list.
+    fn parse_stmt_expr(&mut self, is_end: fn(&TokenType) -> bool) -> SyntaxResultList<Result<Stmt, Box<Expr>>> {
+        let token = self.lexer.get_token(&self.lexer_ctx)?;
+        let expr = self.parse_expr_nud(&token)?;
+        // A block-like expression ends the statement, so that `if c {} -x;` is two statements.
+        let expr = if Self::is_block_like(&expr) { expr } else { self.parse_expr_infix(expr, 0)? };
+        self.skip_doc_comments()?;
+        let token = self.lexer.peek_token(&self.lexer_ctx)?;
+        match &token.token_type {
+            TokenType::Assign(assign) => {
+                let assign = *assign;
+                self.lexer.get_token(&self.lexer_ctx)?;
+                Ok(Ok(self.parse_stmt_assign(expr, assign)?))
+            }
+            TokenType::Punctuation(TokenPunctuation::Semicolon) => {
+                let semicolon = self.lexer.get_token(&self.lexer_ctx)?;
+                Ok(Ok(Stmt {
+                    pos: Position::combine(&expr.pos, &semicolon.pos),
+                    stmt_type: StmtType::Expr(StmtExpr { expr }),
+                }))
+            }
+            token_type if is_end(token_type) => Ok(Err(expr)),
+            _ if Self::is_block_like(&expr) => Ok(Ok(Stmt {
+                pos: expr.pos.clone(),
+                stmt_type: StmtType::Expr(StmtExpr { expr }),
+            })),
+            _ => Err(SyntaxError::new(token.pos.clone(), "Expect ';'.".to_string()).into()),
+        }
+    }
+
+    /// Record the errors of a failed statement and skip the rest of it, returning the position of the first error.
+    fn recover(&mut self, err: SyntaxErrorList) -> Position {
+        let pos = err.list[0].pos.clone();
+        self.errors.combine(err);
+        self.synchronize(pos.end_line);
+        pos
+    }
+
+    /// Skip tokens after a syntax error on `line` until a point where parsing can resume: just after a ';',
+    /// or before a '}' closing the current block, a keyword starting a statement, a token on a later line
+    /// or the end of the file. Blocks met on the way are skipped as a whole.
+    fn synchronize(&mut self, line: usize) {
+        let mut depth = 0usize;
+        loop {
+            let token = match self.lexer.peek_token(&self.lexer_ctx) {
+                Ok(token) => token,
+                Err(err) => {
+                    self.errors.combine(err.into());
+                    continue;
+                }
+            };
+            match &token.token_type {
+                TokenType::Eof => return,
+                TokenType::Paren(TokenParen::LeftBrace) => depth += 1,
+                TokenType::Paren(TokenParen::RightBrace) if depth == 0 => return,
+                TokenType::Paren(TokenParen::RightBrace) => depth -= 1,
+                TokenType::Punctuation(TokenPunctuation::Semicolon) if depth == 0 => {
+                    let _ = self.lexer.get_token(&self.lexer_ctx);
+                    return;
+                }
+                TokenType::Keyword(
+                    TokenKeyword::Let | TokenKeyword::Fn | TokenKeyword::Return | TokenKeyword::Break | TokenKeyword::Continue
+                    | TokenKeyword::If | TokenKeyword::While | TokenKeyword::Loop
+                ) if depth == 0 => return,
+                _ if depth == 0 && token.pos.start_line > line => return,
+                _ => {}
+            }
+            let _ = self.lexer.get_token(&self.lexer_ctx);
+        }
+    }
+
+    /// The node standing for an expression or a statement that failed to parse.
+    #[must_use]
+    fn error_expr(pos: Position) -> Box<Expr> {
+        Box::new(Expr {
+            pos,
+            expr_type: ExprType::Error,
+            type_id: TypeId::ERROR,
+        })
+    }
+
+    /// Skip the doc comments in front of the next token, as they are not attached to declarations yet.
+    fn skip_doc_comments(&mut self) -> SyntaxResultList<()> {
+        while let TokenType::DocComment(_) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Whether an expression ends with a block, so that it needs no ';' to become a statement.
+    #[must_use]
+    fn is_block_like(expr: &Expr) -> bool {
+        matches!(expr.expr_type, ExprType::Block(_) | ExprType::If(_) | ExprType::While(_) | ExprType::Loop(_))
+    }
+
+    fn parse_stmt_fn(&mut self) -> SyntaxResultList<Stmt> {
+        let fn_token = self.lexer.get_token(&self.lexer_ctx)?;
+
+        let name_token = self.lexer.get_token(&self.lexer_ctx)?;
+        let TokenType::Identifier(name) = name_token.token_type else {
+            return Err(SyntaxError::new(name_token.pos, "Expect a function name.".to_string()).into());
+        };
+
+        self.expect_paren(TokenParen::LeftParen, "Expect '('.")?;
+        let mut params = vec![];
+        while !matches!(self.lexer.peek_token(&self.lexer_ctx)?.token_type, TokenType::Paren(TokenParen::RightParen)) {
+            let mutable = if let TokenType::Keyword(TokenKeyword::Mut) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+                self.lexer.get_token(&self.lexer_ctx)?;
+                true
+            } else {
+                false
+            };
+
+            let param_token = self.lexer.get_token(&self.lexer_ctx)?;
+            let TokenType::Identifier(param_name) = param_token.token_type else {
+                return Err(SyntaxError::new(param_token.pos, "Expect a parameter name.".to_string()).into());
+            };
+            self.expect_punctuation(TokenPunctuation::Colon, "Expect ':'.")?;
+            let param_type = self.parse_type()?;
+            params.push(FnParam {
+                name: param_name,
+                pos: param_token.pos,
+                mutable,
+                param_type,
+            });
+
+            if let TokenType::Punctuation(TokenPunctuation::Comma) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+                self.lexer.get_token(&self.lexer_ctx)?;
+            } else {
+                break;
+            }
+        }
+        self.expect_paren(TokenParen::RightParen, "Expect ')'.")?;
+
+        let return_type = if let TokenType::Punctuation(TokenPunctuation::Arrow) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        let brace_token = self.expect_paren(TokenParen::LeftBrace, "Expect '{'.")?;
+        let body = self.parse_expr_block(&brace_token)?;
+
+        Ok(Stmt {
+            pos: Position::combine(&fn_token.pos, &body.pos),
+            stmt_type: StmtType::Fn(StmtFn {
+                name,
+                name_pos: name_token.pos,
+                params,
+                return_type,
+                body,
+            }),
+        })
+    }
+
+    fn parse_stmt_return(&mut self) -> SyntaxResultList<Stmt> {
+        let return_token = self.lexer.get_token(&self.lexer_ctx)?;
+
+        let value = if let TokenType::Punctuation(TokenPunctuation::Semicolon) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            None
+        } else {
+            Some(self.parse_expression(0)?)
+        };
+        let semicolon = self.expect_punctuation(TokenPunctuation::Semicolon, "Expect ';'.")?;
+
+        Ok(Stmt {
+            pos: Position::combine(&return_token.pos, &semicolon.pos),
+            stmt_type: StmtType::Return(StmtReturn { value }),
+        })
+    }
+
+    fn parse_stmt_let(&mut self) -> SyntaxResultList<Stmt> {
+        let let_token = self.lexer.get_token(&self.lexer_ctx)?;
+
+        let mutable = if let TokenType::Keyword(TokenKeyword::Mut) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            true
+        } else {
+            false
+        };
+
+        let name_token = self.lexer.get_token(&self.lexer_ctx)?;
+        let TokenType::Identifier(name) = name_token.token_type else {
+            return Err(SyntaxError::new(name_token.pos, "Expect a variable name.".to_string()).into());
+        };
+
+        let var_type = if let TokenType::Punctuation(TokenPunctuation::Colon) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+
+        let assign_token = self.lexer.get_token(&self.lexer_ctx)?;
+        if !matches!(assign_token.token_type, TokenType::Assign(TokenAssign::Assign)) {
+            return Err(SyntaxError::new(assign_token.pos, "Expect '='.".to_string()).into());
+        }
+
+        // The variable is still declared when its initializer is broken, so that its uses report no more errors.
+        let (init, end_pos) = match self.parse_expression(0)
+            .and_then(|init| Ok((init, self.expect_punctuation(TokenPunctuation::Semicolon, "Expect ';'.")?))) {
+            Ok((init, semicolon)) => (init, semicolon.pos),
+            Err(err) => {
+                let pos = self.recover(err);
+                (Self::error_expr(pos.clone()), pos)
+            }
+        };
+
+        Ok(Stmt {
+            pos: Position::combine(&let_token.pos, &end_pos),
+            stmt_type: StmtType::Let(StmtLet {
+                name,
+                name_pos: name_token.pos,
+                mutable,
+                var_type,
+                init,
+            }),
+        })
+    }
+
+    fn parse_stmt_assign(&mut self, target: Box<Expr>, assign: TokenAssign) -> SyntaxResultList<Stmt> {
+        let ope = match assign {
+            TokenAssign::Assign => None,
+            TokenAssign::PlusAssign => Some(BinaryOperator::Plus),
+            TokenAssign::MinusAssign => Some(BinaryOperator::Minus),
+            TokenAssign::MultiplyAssign => Some(BinaryOperator::Multiply),
+            TokenAssign::DivideAssign => Some(BinaryOperator::Divide),
+            TokenAssign::RemainderAssign => Some(BinaryOperator::Remainder),
+            TokenAssign::PowerAssign => Some(BinaryOperator::Power),
+            TokenAssign::BitAndAssign => Some(BinaryOperator::BitAnd),
+            TokenAssign::BitOrAssign => Some(BinaryOperator::BitOr),
+            TokenAssign::BitXorAssign => Some(BinaryOperator::BitXor),
+            TokenAssign::ShiftLeftAssign => Some(BinaryOperator::ShiftLeft),
+            TokenAssign::ShiftRightAssign => Some(BinaryOperator::ShiftRight),
+        };
+
+        let value = self.parse_expression(0)?;
+        let semicolon = self.expect_punctuation(TokenPunctuation::Semicolon, "Expect ';'.")?;
+
+        Ok(Stmt {
+            pos: Position::combine(&target.pos, &semicolon.pos),
+            stmt_type: StmtType::Assign(StmtAssign {
+                ope,
+                target,
+                value,
+            }),
+        })
+    }
+
+    fn parse_type(&mut self) -> SyntaxResultList<TypeExpr> {
+        let token = self.lexer.get_token(&self.lexer_ctx)?;
+        match token.token_type {
+            TokenType::Identifier(name) => Ok(TypeExpr {
+                pos: token.pos,
+                type_expr_type: TypeExprType::Named(name),
+                type_id: TypeId::ERROR,
+            }),
+            TokenType::Operator(operator @ (TokenOperator::BitAnd | TokenOperator::And)) => {
+                let mutable = self.parse_mut()?;
+                let target = self.parse_type()?;
+                let mut type_expr = TypeExpr {
+                    pos: Position::combine(&token.pos, &target.pos),
+                    type_expr_type: TypeExprType::Ref { mutable, target: Box::new(target) },
+                    type_id: TypeId::ERROR,
+                };
+                // `&&` is lexed as one token, but stands for two references here.
+                if operator == TokenOperator::And {
+                    type_expr = TypeExpr {
+                        pos: type_expr.pos.clone(),
+                        type_expr_type: TypeExprType::Ref { mutable: false, target: Box::new(type_expr) },
+                        type_id: TypeId::ERROR,
+                    };
+                }
+                Ok(type_expr)
+            }
+            _ => Err(SyntaxError::new(token.pos, "Expect a type.".to_string()).into()),
+        }
+    }
+
+    /// Parse the `mut` that may follow a '&', telling whether it is there.
+    fn parse_mut(&mut self) -> SyntaxResultList<bool> {
+        if let TokenType::Keyword(TokenKeyword::Mut) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect_punctuation(&mut self, punctuation: TokenPunctuation, msg: &str) -> SyntaxResultList<Token> {
+        // A wrong token is left in the lexer, as it may well start the code that error recovery resumes from.
+        let token = self.lexer.peek_token(&self.lexer_ctx)?;
+        match token.token_type {
+            TokenType::Punctuation(found) if found == punctuation => Ok(self.lexer.get_token(&self.lexer_ctx)?),
+            _ => Err(SyntaxError::new(token.pos.clone(), msg.to_string()).into()),
+        }
+    }
+
+    fn parse_stmt_break(&mut self) -> SyntaxResultList<Stmt> {
+        let break_token = self.lexer.get_token(&self.lexer_ctx)?;
+        let label = self.parse_optional_label()?;
+
+        let value = if let TokenType::Punctuation(TokenPunctuation::Semicolon) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            None
+        } else {
+            Some(self.parse_expression(0)?)
+        };
+        let semicolon = self.expect_punctuation(TokenPunctuation::Semicolon, "Expect ';'.")?;
+
+        Ok(Stmt {
+            pos: Position::combine(&break_token.pos, &semicolon.pos),
+            stmt_type: StmtType::Break(StmtBreak { label, value }),
+        })
+    }
+
+    fn parse_stmt_continue(&mut self) -> SyntaxResultList<Stmt> {
+        let continue_token = self.lexer.get_token(&self.lexer_ctx)?;
+        let label = self.parse_optional_label()?;
+        let semicolon = self.expect_punctuation(TokenPunctuation::Semicolon, "Expect ';'.")?;
+
+        Ok(Stmt {
+            pos: Position::combine(&continue_token.pos, &semicolon.pos),
+            stmt_type: StmtType::Continue(StmtContinue { label }),
+        })
+    }
+
+    fn parse_optional_label(&mut self) -> SyntaxResultList<Option<Symbol>> {
+        if let TokenType::Label(label) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            Ok(Some(label))
+        } else {
+            Ok(None)
         }
     }
 
-    pub fn parse(&mut self) -> SyntaxResultList<Box<Expr>> {
-        self.parse_expression(0)
+    fn expect_paren(&mut self, paren: TokenParen, msg: &str) -> SyntaxResultList<Token> {
+        let token = self.lexer.peek_token(&self.lexer_ctx)?;
+        match token.token_type {
+            TokenType::Paren(found) if found == paren => Ok(self.lexer.get_token(&self.lexer_ctx)?),
+            _ => Err(SyntaxError::new(token.pos.clone(), msg.to_string()).into()),
+        }
     }
 
     fn parse_expression(&mut self, last_rbp: u32) -> SyntaxResultList<Box<Expr>> {
+        let token = self.lexer.peek_token(&self.lexer_ctx)?;
+        // A '}' closes the enclosing block even where an expression is missing, so it is left for the block to take.
+        if let TokenType::Paren(TokenParen::RightBrace) = token.token_type {
+            return Err(SyntaxError::new(token.pos.clone(), "Unexpected token in an expression.".to_string()).into());
+        }
         let token = self.lexer.get_token(&self.lexer_ctx)?;
-        let mut left = self.parse_expr_nud(&token)?;
+        let left = self.parse_expr_nud(&token)?;
+        self.parse_expr_infix(left, last_rbp)
+    }
 
+    /// Parse the operators following `left` that bind tighter than `last_rbp`.
+    fn parse_expr_infix(&mut self, mut left: Box<Expr>, last_rbp: u32) -> SyntaxResultList<Box<Expr>> {
         let mut current_token = self.lexer.peek_token(&self.lexer_ctx)?;
         while !current_token.is_eof() {
             match &current_token.token_type {
                 TokenType::Operator(ope) => {
                     let operator = ope.clone();
-                    let (lbp, rbp) = self.precedence[ope];
+                    let Some(&(lbp, rbp)) = self.precedence.get(ope) else {
+                        break;
+                    };
                     if lbp < last_rbp {
                         break;
                     }
@@ -76,7 +538,29 @@ impl<'a> Parser<'a> {
                     self.lexer.get_token(&self.lexer_ctx)?;
                     left = self.parse_expr_led(&pos, &operator, left, rbp)?;
                 }
-                _ => return Err(SyntaxError::new(self.lexer.peek_token(&self.lexer_ctx)?.pos.clone(), "Unexpected token in an expression.".to_string()).into()),
+                TokenType::Paren(TokenParen::LeftParen) => {
+                    if Self::POSTFIX_BP < last_rbp {
+                        break;
+                    }
+                    self.lexer.get_token(&self.lexer_ctx)?;
+                    left = self.parse_expr_call(left)?;
+                }
+                TokenType::Keyword(TokenKeyword::As) => {
+                    if Self::CAST_BP < last_rbp {
+                        break;
+                    }
+                    self.lexer.get_token(&self.lexer_ctx)?;
+                    let target = self.parse_type()?;
+                    left = Box::new(Expr {
+                        pos: Position::combine(&left.pos, &target.pos),
+                        expr_type: ExprType::Cast(ExprCast {
+                            expr: left,
+                            target,
+                        }),
+                        type_id: TypeId::ERROR,
+                    });
+                }
+                _ => break,
             }
             current_token = self.lexer.peek_token(&self.lexer_ctx)?;
         }
@@ -84,14 +568,126 @@ impl<'a> Parser<'a> {
         Ok(left)
     }
 
+    fn parse_expr_call(&mut self, callee: Box<Expr>) -> SyntaxResultList<Box<Expr>> {
+        let mut args = vec![];
+        while !matches!(self.lexer.peek_token(&self.lexer_ctx)?.token_type, TokenType::Paren(TokenParen::RightParen)) {
+            args.push(*self.parse_expression(0)?);
+            if let TokenType::Punctuation(TokenPunctuation::Comma) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+                self.lexer.get_token(&self.lexer_ctx)?;
+            } else {
+                break;
+            }
+        }
+        let paren_token = self.expect_paren(TokenParen::RightParen, "Expect ')'.")?;
+
+        Ok(Box::new(Expr {
+            pos: Position::combine(&callee.pos, &paren_token.pos),
+            expr_type: ExprType::Call(ExprCall {
+                callee,
+                args,
+            }),
+            type_id: TypeId::ERROR,
+        }))
+    }
+
+    /// Parse the rest of a block whose '{' has been consumed.
+    fn parse_expr_block(&mut self, brace_token: &Token) -> SyntaxResultList<Box<Expr>> {
+        let (stmts, tail) = self.parse_stmt_list(|token_type| matches!(token_type, TokenType::Paren(TokenParen::RightBrace)));
+        let end_token = self.lexer.get_token(&self.lexer_ctx)?;
+        Ok(Box::new(Expr {
+            pos: Position::combine(&brace_token.pos, &end_token.pos),
+            expr_type: ExprType::Block(ExprBlock {
+                stmts,
+                tail,
+            }),
+            type_id: TypeId::ERROR,
+        }))
+    }
+
+    /// Parse the rest of an `if` expression whose `if` has been consumed.
+    fn parse_expr_if(&mut self, if_token: &Token) -> SyntaxResultList<Box<Expr>> {
+        let cond = self.parse_expression(0)?;
+        let brace_token = self.expect_paren(TokenParen::LeftBrace, "Expect '{'.")?;
+        let then_branch = self.parse_expr_block(&brace_token)?;
+
+        let else_branch = if let TokenType::Keyword(TokenKeyword::Else) = self.lexer.peek_token(&self.lexer_ctx)?.token_type {
+            self.lexer.get_token(&self.lexer_ctx)?;
+            let token = self.lexer.get_token(&self.lexer_ctx)?;
+            match token.token_type {
+                TokenType::Keyword(TokenKeyword::If) => Some(self.parse_expr_if(&token)?),
+                TokenType::Paren(TokenParen::LeftBrace) => Some(self.parse_expr_block(&token)?),
+                _ => return Err(SyntaxError::new(token.pos, "Expect '{' or 'if' after 'else'.".to_string()).into()),
+            }
+        } else {
+            None
+        };
+
+        let end_pos = else_branch.as_ref().map_or(&then_branch.pos, |else_branch| &else_branch.pos).clone();
+        Ok(Box::new(Expr {
+            pos: Position::combine(&if_token.pos, &end_pos),
+            expr_type: ExprType::If(ExprIf {
+                cond,
+                then_branch,
+                else_branch,
+            }),
+            type_id: TypeId::ERROR,
+        }))
+    }
+
+    /// Parse the rest of a `while` or `loop` expression whose keyword has been consumed.
+    fn parse_expr_loop(&mut self, start_pos: &Position, keyword: TokenKeyword, label: Option<Symbol>) -> SyntaxResultList<Box<Expr>> {
+        let cond = if let TokenKeyword::While = keyword {
+            Some(self.parse_expression(0)?)
+        } else {
+            None
+        };
+        let brace_token = self.expect_paren(TokenParen::LeftBrace, "Expect '{'.")?;
+        let body = self.parse_expr_block(&brace_token)?;
+
+        Ok(Box::new(Expr {
+            pos: Position::combine(start_pos, &body.pos),
+            expr_type: match cond {
+                Some(cond) => ExprType::While(ExprWhile {
+                    label,
+                    cond,
+                    body,
+                }),
+                None => ExprType::Loop(ExprLoop {
+                    label,
+                    body,
+                }),
+            },
+            type_id: TypeId::ERROR,
+        }))
+    }
+
     fn parse_expr_nud(&mut self, token: &Token) -> SyntaxResultList<Box<Expr>> {
         match &token.token_type {
             TokenType::Literal(literal) => Ok(Box::new(Expr {
                 pos: token.pos.clone(),
                 expr_type: ExprType::Literal(ExprLiteral {
-                    value: Self::token_literal_to_value(&literal),
-                })
+                    value: Self::token_literal_to_value(literal),
+                }),
+                type_id: TypeId::ERROR,
             })),
+            TokenType::Identifier(name) => Ok(Box::new(Expr {
+                pos: token.pos.clone(),
+                expr_type: ExprType::Variable(ExprVariable {
+                    name: *name,
+                }),
+                type_id: TypeId::ERROR,
+            })),
+            TokenType::Paren(TokenParen::LeftBrace) => self.parse_expr_block(token),
+            TokenType::Keyword(TokenKeyword::If) => self.parse_expr_if(token),
+            TokenType::Keyword(keyword @ (TokenKeyword::While | TokenKeyword::Loop)) => self.parse_expr_loop(&token.pos, *keyword, None),
+            TokenType::Label(label) => {
+                self.expect_punctuation(TokenPunctuation::Colon, "Expect ':' after a label.")?;
+                let keyword_token = self.lexer.get_token(&self.lexer_ctx)?;
+                match keyword_token.token_type {
+                    TokenType::Keyword(keyword @ (TokenKeyword::While | TokenKeyword::Loop)) => self.parse_expr_loop(&token.pos, keyword, Some(*label)),
+                    _ => Err(SyntaxError::new(keyword_token.pos, "Expect 'while' or 'loop' after a label.".to_string()).into()),
+                }
+            }
             TokenType::Paren(TokenParen::LeftParen) => {
                 let expr = self.parse_expression(0)?;
                 if !matches!(self.lexer.peek_token(&self.lexer_ctx)?.token_type, TokenType::Paren(TokenParen::RightParen)) {
@@ -102,28 +698,57 @@ impl<'a> Parser<'a> {
                 Ok(Box::new(Expr {
                     pos: Position::combine(&token.pos, &paren_token.pos),
                     expr_type: expr.expr_type,
+                    type_id: TypeId::ERROR,
                 }))
             }
-            TokenType::Operator(TokenOperator::Plus) => {
-                let (lbp, _rbp) = self.precedence[&TokenOperator::Plus];
-                let expr = self.parse_expression(lbp)?;
+            TokenType::Keyword(keyword @ (TokenKeyword::True | TokenKeyword::False)) => Ok(Box::new(Expr {
+                pos: token.pos.clone(),
+                expr_type: ExprType::Literal(ExprLiteral {
+                    value: Value::Bool(*keyword == TokenKeyword::True),
+                }),
+                type_id: TypeId::ERROR,
+            })),
+            TokenType::Operator(operator @ (TokenOperator::Plus | TokenOperator::Minus | TokenOperator::Not | TokenOperator::BitNot)) => {
+                let ope = match operator {
+                    TokenOperator::Plus => UnaryOperator::Plus,
+                    TokenOperator::Minus => UnaryOperator::Minus,
+                    TokenOperator::Not => UnaryOperator::Not,
+                    _ => UnaryOperator::BitNot,
+                };
+                let expr = self.parse_expression(Self::PREFIX_BP)?;
                 Ok(Box::new(Expr {
                     pos: Position::combine(&token.pos, &expr.pos),
                     expr_type: ExprType::Unary(ExprUnary {
-                        ope: UnaryOperator::Plus,
+                        ope,
                         rhs: expr,
-                    })
+                    }),
+                    type_id: TypeId::ERROR,
                 }))
             }
-            TokenType::Operator(TokenOperator::Minus) => {
-                let (lbp, _rbp) = self.precedence[&TokenOperator::Minus];
-                let expr = self.parse_expression(lbp)?;
+            TokenType::Operator(operator @ (TokenOperator::BitAnd | TokenOperator::And)) => {
+                let mutable = self.parse_mut()?;
+                let expr = self.parse_expression(Self::REF_BP)?;
+                let mut borrow = Box::new(Expr {
+                    pos: Position::combine(&token.pos, &expr.pos),
+                    expr_type: ExprType::Borrow(ExprBorrow { mutable, expr }),
+                    type_id: TypeId::ERROR,
+                });
+                // `&&` is lexed as one token, but stands for two borrows here.
+                if *operator == TokenOperator::And {
+                    borrow = Box::new(Expr {
+                        pos: borrow.pos.clone(),
+                        expr_type: ExprType::Borrow(ExprBorrow { mutable: false, expr: borrow }),
+                        type_id: TypeId::ERROR,
+                    });
+                }
+                Ok(borrow)
+            }
+            TokenType::Operator(TokenOperator::Multiply) => {
+                let expr = self.parse_expression(Self::REF_BP)?;
                 Ok(Box::new(Expr {
                     pos: Position::combine(&token.pos, &expr.pos),
-                    expr_type: ExprType::Unary(ExprUnary {
-                        ope: UnaryOperator::Minus,
-                        rhs: expr,
-                    })
+                    expr_type: ExprType::Deref(ExprDeref { expr }),
+                    type_id: TypeId::ERROR,
                 }))
             }
             _ => Err(SyntaxError::new(token.pos.clone(), "Unexpected token in an expression.".to_string()).into()),
@@ -131,63 +756,39 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expr_led(&mut self, _pos: &Position, operator: &TokenOperator, lhs: Box<Expr>, rbp: u32) -> SyntaxResultList<Box<Expr>> {
-        match operator {
-            TokenOperator::Plus => {
-                let rhs = self.parse_expression(rbp)?;
-                Ok(Box::new(Expr {
-                    pos: Position::combine(&lhs.pos, &rhs.pos),
-                    expr_type: ExprType::Binary(ExprBinary {
-                        ope: BinaryOperator::Plus,
-                        lhs,
-                        rhs,
-                    })
-                }))
-            }
-            TokenOperator::Minus => {
-                let rhs = self.parse_expression(rbp)?;
-                Ok(Box::new(Expr {
-                    pos: Position::combine(&lhs.pos, &rhs.pos),
-                    expr_type: ExprType::Binary(ExprBinary {
-                        ope: BinaryOperator::Minus,
-                        lhs,
-                        rhs,
-                    })
-                }))
-            }
-            TokenOperator::Multiply => {
-                let rhs = self.parse_expression(rbp)?;
-                Ok(Box::new(Expr {
-                    pos: Position::combine(&lhs.pos, &rhs.pos),
-                    expr_type: ExprType::Binary(ExprBinary {
-                        ope: BinaryOperator::Multiply,
-                        lhs,
-                        rhs,
-                    })
-                }))
-            }
-            TokenOperator::Divide => {
-                let rhs = self.parse_expression(rbp)?;
-                Ok(Box::new(Expr {
-                    pos: Position::combine(&lhs.pos, &rhs.pos),
-                    expr_type: ExprType::Binary(ExprBinary {
-                        ope: BinaryOperator::Divide,
-                        lhs,
-                        rhs,
-                    })
-                }))
-            }
-            TokenOperator::Power => {
-                let rhs = self.parse_expression(rbp)?;
-                Ok(Box::new(Expr {
-                    pos: Position::combine(&lhs.pos, &rhs.pos),
-                    expr_type: ExprType::Binary(ExprBinary {
-                        ope: BinaryOperator::Power,
-                        lhs,
-                        rhs,
-                    })
-                }))
-            }
-        }
+        let ope = match operator {
+            TokenOperator::Plus => BinaryOperator::Plus,
+            TokenOperator::Minus => BinaryOperator::Minus,
+            TokenOperator::Multiply => BinaryOperator::Multiply,
+            TokenOperator::Divide => BinaryOperator::Divide,
+            TokenOperator::Remainder => BinaryOperator::Remainder,
+            TokenOperator::Power => BinaryOperator::Power,
+            TokenOperator::Equal => BinaryOperator::Equal,
+            TokenOperator::NotEqual => BinaryOperator::NotEqual,
+            TokenOperator::Less => BinaryOperator::Less,
+            TokenOperator::LessEqual => BinaryOperator::LessEqual,
+            TokenOperator::Greater => BinaryOperator::Greater,
+            TokenOperator::GreaterEqual => BinaryOperator::GreaterEqual,
+            TokenOperator::And => BinaryOperator::And,
+            TokenOperator::Or => BinaryOperator::Or,
+            TokenOperator::BitAnd => BinaryOperator::BitAnd,
+            TokenOperator::BitOr => BinaryOperator::BitOr,
+            TokenOperator::BitXor => BinaryOperator::BitXor,
+            TokenOperator::ShiftLeft => BinaryOperator::ShiftLeft,
+            TokenOperator::ShiftRight => BinaryOperator::ShiftRight,
+            TokenOperator::Not | TokenOperator::BitNot => unreachable!("'!' and '~' are prefix operators only."),
+        };
+
+        let rhs = self.parse_expression(rbp)?;
+        Ok(Box::new(Expr {
+            pos: Position::combine(&lhs.pos, &rhs.pos),
+            expr_type: ExprType::Binary(ExprBinary {
+                ope,
+                lhs,
+                rhs,
+            }),
+            type_id: TypeId::ERROR,
+        }))
     }
 
     fn token_literal_to_value(literal: &TokenLiteral) -> Value {
@@ -204,14 +805,120 @@ impl<'a> Parser<'a> {
                     TokenInteger::UInt64(int) => Value::Integer(ValueInteger::UInt64(*int)),
                     TokenInteger::Int128(int) => Value::Integer(ValueInteger::Int128(*int)),
                     TokenInteger::UInt128(int) => Value::Integer(ValueInteger::UInt128(*int)),
+                    TokenInteger::Untyped(int) => Value::Integer(ValueInteger::Untyped(*int)),
                 }
             }
             TokenLiteral::Float(float) => {
                 match float {
                     TokenFloat::Float32(float) => Value::Float(ValueFloat::Float32(*float)),
                     TokenFloat::Float64(float) => Value::Float(ValueFloat::Float64(*float)),
+                    TokenFloat::Untyped(float) => Value::Float(ValueFloat::Untyped(*float)),
                 }
             }
+            TokenLiteral::Str(string) => Value::Str(string.clone()),
+            TokenLiteral::Char(ch) => Value::Char(*ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Program {
+        let source = source.to_string();
+        let arg_context = ArgContext { option_args: vec![], file_name: None };
+        let mut parser = Parser::new(&source, &arg_context, SymbolInterner::create());
+        let (program, errors) = parser.parse();
+        if let Some(err) = errors.list.first() {
+            panic!("unexpected syntax error: {}", err.msg);
         }
+        program
+    }
+
+    /// Parse `source`, returning the lines and messages of the syntax errors with the number of statements kept.
+    fn parse_errors(source: &str) -> (Vec<(usize, String)>, usize) {
+        let source = source.to_string();
+        let arg_context = ArgContext { option_args: vec![], file_name: None };
+        let mut parser = Parser::new(&source, &arg_context, SymbolInterner::create());
+        let (program, errors) = parser.parse();
+        (errors.list.into_iter().map(|err| (err.pos.start_line, err.msg)).collect(), program.stmts.len())
+    }
+
+    fn stmt_expr(stmt: &Stmt) -> &ExprType {
+        match &stmt.stmt_type {
+            StmtType::Expr(stmt_expr) => &stmt_expr.expr.expr_type,
+            _ => panic!("expected an expression statement"),
+        }
+    }
+
+    #[test]
+    fn loop_ends_statement() {
+        let program = parse("loop { break; } -1;");
+        assert_eq!(program.stmts.len(), 2);
+        assert!(matches!(stmt_expr(&program.stmts[0]), ExprType::Loop(_)));
+        assert!(matches!(stmt_expr(&program.stmts[1]), ExprType::Unary(_)));
+    }
+
+    #[test]
+    fn if_ends_statement() {
+        let program = parse("let x = 1; if x { x; } -x;");
+        assert_eq!(program.stmts.len(), 3);
+        assert!(matches!(stmt_expr(&program.stmts[1]), ExprType::If(_)));
+        assert!(matches!(stmt_expr(&program.stmts[2]), ExprType::Unary(_)));
+    }
+
+    #[test]
+    fn while_ends_statement() {
+        let program = parse("let a = 1; while 0 {} (a);");
+        assert_eq!(program.stmts.len(), 3);
+        assert!(matches!(stmt_expr(&program.stmts[1]), ExprType::While(_)));
+        assert!(matches!(stmt_expr(&program.stmts[2]), ExprType::Variable(_)));
+    }
+
+    #[test]
+    fn block_like_expression_as_operand() {
+        let program = parse("let x = if 1 { 2 } else { 3 } - 1;");
+        let StmtType::Let(stmt_let) = &program.stmts[0].stmt_type else {
+            panic!("expected a let statement");
+        };
+        assert!(matches!(stmt_let.init.expr_type, ExprType::Binary(_)));
+    }
+
+    #[test]
+    fn block_like_tail_expression() {
+        let program = parse("if 1 { 2 } else { 3 }");
+        assert!(program.stmts.is_empty());
+        assert!(matches!(program.tail.map(|tail| tail.expr_type), Some(ExprType::If(_))));
+    }
+
+    #[test]
+    fn reports_every_syntax_error() {
+        let (errors, stmt_cnt) = parse_errors("let x = ;\nlet y = 1 +;\nlet z = 3;");
+        assert_eq!(errors, [
+            (1, "Unexpected token in an expression.".to_string()),
+            (2, "Unexpected token in an expression.".to_string()),
+        ]);
+        assert_eq!(stmt_cnt, 3);
+    }
+
+    #[test]
+    fn missing_semicolon_resumes_on_next_line() {
+        let (errors, stmt_cnt) = parse_errors("let a = 1\nlet b = 2;\nlet c = ;");
+        assert_eq!(errors, [(2, "Expect ';'.".to_string()), (3, "Unexpected token in an expression.".to_string())]);
+        assert_eq!(stmt_cnt, 3);
+    }
+
+    #[test]
+    fn error_inside_block_stays_in_block() {
+        let (errors, stmt_cnt) = parse_errors("fn f() {\n    let a = ;\n}\nlet b = 2;");
+        assert_eq!(errors, [(2, "Unexpected token in an expression.".to_string())]);
+        assert_eq!(stmt_cnt, 2);
+    }
+
+    #[test]
+    fn unbalanced_braces() {
+        assert_eq!(parse_errors("let a = 1; } let b = 2;").0, [(1, "Unexpected '}'.".to_string())]);
+        assert_eq!(parse_errors("fn f() { loop { let a = 1;").0, [(1, "Expect '}'.".to_string())]);
     }
 }
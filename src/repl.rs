@@ -0,0 +1,306 @@
+//! The interactive mode of Iranta, for `iranta repl`.
+//!
+//! Each input is compiled into a module of its own and added to a JIT kept for the whole session, so that only the
+//! newest input runs. Its top-level variables are globals, which later inputs link to together with its functions.
+//! An input that panics is left out like one with errors, and the session goes on.
+
+use std::collections::HashMap;
+use std::io::Write;
+use inkwell::context::Context;
+use inkwell::execution_engine::ExecutionEngine;
+use crate::analyzer::Analyzer;
+use crate::analyzer::borrowck::{BorrowChecker, GlobalLoan};
+use crate::args::ArgContext;
+use crate::compiler::Compiler;
+use crate::compiler::jit::{ReplFunction, ReplGlobal};
+use crate::compiler_data::data_type::TypeInterner;
+use crate::compiler_data::position::Position;
+use crate::compiler_data::symbol::{Symbol, SymbolInterner};
+use crate::compiler_data::value::Value;
+use crate::main_error::{print_error, CopySource};
+use crate::mir::lower::MirBuilder;
+use crate::parser::Parser;
+
+pub fn run_repl(arg_context: &ArgContext) {
+    println!("Enter ':quit' or press Ctrl-D to leave.");
+
+    let llvm_context = Context::create();
+    let mut session = match Session::new(&llvm_context, arg_context) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            return;
+        }
+    };
+    while let Some(input) = read_input() {
+        match input.trim() {
+            "" => continue,
+            ":quit" | ":q" => break,
+            _ => (),
+        }
+
+        if let Ok(Some(value)) = session.evaluate(&input) {
+            println!("{}", value);
+        }
+    }
+}
+
+/// What a REPL session keeps from one input to the next.
+struct Session<'a, 'ctx> {
+    arg_context: &'a ArgContext,
+    llvm_ctx: &'ctx Context,
+    engine: ExecutionEngine<'ctx>,
+    symbols: SymbolInterner,
+    type_interner: TypeInterner,
+    /// The top-level variables of the inputs run so far, the newest binding of each name.
+    globals: HashMap<Symbol, ReplGlobal>,
+    /// Where the value of each top-level variable was moved, if it was.
+    moves: HashMap<Symbol, Option<Position>>,
+    /// The loans held by each top-level variable, which last for the rest of the session.
+    loans: HashMap<Symbol, Vec<GlobalLoan>>,
+    functions: Vec<ReplFunction>,
+    /// The inputs so far, which the lines of the newest one are counted after so that moves in them can be pointed at.
+    history: String,
+    input_cnt: usize,
+}
+
+impl<'a, 'ctx> Session<'a, 'ctx> {
+    fn new(llvm_ctx: &'ctx Context, arg_context: &'a ArgContext) -> Result<Self, String> {
+        Ok(Self {
+            arg_context,
+            llvm_ctx,
+            engine: Compiler::create_repl_engine(llvm_ctx, arg_context)?,
+            symbols: SymbolInterner::create(),
+            type_interner: TypeInterner::create(),
+            globals: HashMap::new(),
+            moves: HashMap::new(),
+            loans: HashMap::new(),
+            functions: vec![],
+            history: String::new(),
+            input_cnt: 0,
+        })
+    }
+
+    /// Compile and run one input, printing the errors found, and give back the value of its tail expression.
+    fn evaluate(&mut self, input: &str) -> Result<Option<Value>, ()> {
+        let hidden_lines = self.history.matches('\n').count();
+        let source = "\n".repeat(hidden_lines) + input;
+        let history = self.history.clone() + input;
+        let copy_source = CopySource {
+            lines: history.split("\n").collect(),
+            hidden_lines,
+        };
+
+        let symbols = std::mem::replace(&mut self.symbols, SymbolInterner::create());
+        let mut parser = Parser::new(&source, self.arg_context, symbols);
+        let (mut program, syntax_errors) = parser.parse();
+        let has_syntax_errors = !syntax_errors.list.is_empty();
+        for err in syntax_errors.list.into_iter() {
+            print_error("Syntax Error", err, &copy_source);
+        }
+
+        let type_interner = std::mem::replace(&mut self.type_interner, TypeInterner::create());
+        let mut analyzer = Analyzer::new(parser.into_symbols(), type_interner);
+        for global in self.globals.values() {
+            let moved = self.moves.get(&global.name).cloned().flatten();
+            analyzer.declare_global(global.name, global.type_id, global.mutable, moved);
+        }
+        for function in &self.functions {
+            analyzer.declare_extern_function(function.name, function.param_types.clone(), function.return_type);
+        }
+        let result = analyzer.analyze(&mut program, has_syntax_errors);
+        let moves = analyzer.take_global_moves();
+        (self.symbols, self.type_interner) = analyzer.into_parts();
+        if let Err(error) = result {
+            for err in error.list.into_iter() {
+                print_error("Compile Error", err, &copy_source);
+            }
+            return Err(());
+        }
+        if has_syntax_errors {
+            return Err(());
+        }
+
+        let mut mir_builder = MirBuilder::new(&self.type_interner);
+        mir_builder.set_repl();
+        for global in self.globals.values() {
+            mir_builder.declare_global(global.name, global.type_id);
+        }
+        let mir = mir_builder.build(&program);
+        let mut loans = self.loans.clone();
+        let borrow_errors = BorrowChecker::new(&self.symbols, &self.type_interner).check(&mir, Some(&mut loans));
+        if !borrow_errors.is_empty() {
+            for err in borrow_errors.into_iter() {
+                print_error("Compile Error", err, &copy_source);
+            }
+            return Err(());
+        }
+
+        self.input_cnt += 1;
+        let symbols = std::mem::replace(&mut self.symbols, SymbolInterner::create());
+        let type_interner = std::mem::replace(&mut self.type_interner, TypeInterner::create());
+        let mut compiler = Compiler::new(symbols, type_interner, format!("iranta_repl_input_{}", self.input_cnt), self.llvm_ctx);
+        for global in self.globals.values() {
+            compiler.declare_global(global);
+        }
+        for function in &self.functions {
+            compiler.declare_extern_function(function);
+        }
+        compiler.compile(&mir, self.arg_context);
+        let result = compiler.run_repl(&self.engine, self.arg_context);
+        let (globals, functions) = compiler.take_repl_definitions();
+        (self.symbols, self.type_interner) = compiler.into_parts();
+        let value = match result {
+            Ok(Ok(value)) => value,
+            Ok(Err(panic)) => {
+                print_error("Panic", panic, &copy_source);
+                return Err(());
+            }
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                return Err(());
+            }
+        };
+
+        for global in globals {
+            self.globals.insert(global.name, global);
+        }
+        self.moves = moves;
+        self.loans = loans;
+        self.functions.extend(functions);
+        self.history = history;
+        Ok(value)
+    }
+}
+
+/// Read one input from the standard input, going on with more lines while a bracket is left open.
+/// Give back `None` at the end of the input.
+fn read_input() -> Option<String> {
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { "iranta> " } else { "   ...> " });
+        let _ = std::io::stdout().flush();
+
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                println!();
+                return if input.is_empty() { None } else { Some(input) };
+            }
+            Ok(_) => (),
+        }
+        if !line.ends_with('\n') {
+            line.push('\n');
+        }
+        input.push_str(&line);
+
+        if open_brackets(&input) <= 0 {
+            return Some(input);
+        }
+    }
+}
+
+/// Count the brackets left open in `input`, skipping the ones in literals and comments.
+fn open_brackets(input: &str) -> i32 {
+    let mut depth = 0;
+    let mut chars = input.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            '"' => {
+                while let Some(ch) = chars.next() {
+                    match ch {
+                        '\\' => { chars.next(); }
+                        '"' => break,
+                        _ => (),
+                    }
+                }
+            }
+            // A quote starts a character literal only if it is closed right after, otherwise it is a label.
+            '\'' if chars.next_if_eq(&'\\').is_some() => {
+                chars.next();
+                while chars.next_if(|ch| *ch != '\'').is_some() {}
+                chars.next();
+            }
+            '\'' if chars.clone().nth(1) == Some('\'') => {
+                chars.nth(1);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|ch| *ch != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                depth += 1;
+                while let Some(ch) = chars.next() {
+                    match ch {
+                        '/' if chars.next_if_eq(&'*').is_some() => depth += 1,
+                        '*' if chars.next_if_eq(&'/').is_some() => {
+                            depth -= 1;
+                            break;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::OptionArg;
+
+    /// Evaluate the inputs in one session, giving back what each one printed as its value, or `None` on an error.
+    fn run_session(inputs: &[&str]) -> Vec<Option<String>> {
+        let arg_context = ArgContext { option_args: vec![OptionArg::Repl], file_name: None };
+        let llvm_context = Context::create();
+        let mut session = Session::new(&llvm_context, &arg_context).unwrap();
+        inputs.iter().map(|input| {
+            session.evaluate(&format!("{}\n", input)).ok().map(|value| value.map_or(String::new(), |value| value.to_string()))
+        }).collect()
+    }
+
+    fn printed(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn bindings_are_kept_between_inputs() {
+        let results = run_session(&["let mut x = 2;", "x * 21", "x += 1;", "x", "let x = 1.5;", "x"]);
+        assert_eq!(results, [printed(""), printed("int32(42)"), printed(""), printed("int32(3)"), printed(""), printed("float64(1.5)")]);
+    }
+
+    #[test]
+    fn functions_are_kept_between_inputs() {
+        let results = run_session(&["fn double(a: int32) -> int32 { a * 2 }", "double(21)", "fn double(a: int32) -> int32 { a }"]);
+        assert_eq!(results, [printed(""), printed("int32(42)"), None]);
+    }
+
+    #[test]
+    fn failed_input_is_left_out() {
+        let results = run_session(&["let a = 1; let b: bool = a;", "a", "let c = \"str\";", "c"]);
+        assert_eq!(results, [None, None, printed(""), printed("str(\"str\")")]);
+    }
+
+    #[test]
+    fn borrows_are_kept_between_inputs() {
+        let results = run_session(&["let mut x = 1;", "let r = &mut x;", "x", "*r = 2;", "let r = 0;", "x"]);
+        assert_eq!(results, [printed(""), printed(""), None, printed(""), printed(""), printed("int32(2)")]);
+    }
+
+    #[test]
+    fn panicking_input_is_left_out() {
+        let results = run_session(&["let x: int32 = 2147483647;", "let y = x + 1;", "y", "let z = x - 1;", "z"]);
+        assert_eq!(results, [printed(""), None, None, printed(""), printed("int32(2147483646)")]);
+    }
+
+    #[test]
+    fn moves_are_kept_between_inputs() {
+        let results = run_session(&["let s = \"str\";", "let t = s;", "s", "let s = \"new\";", "s"]);
+        assert_eq!(results, [printed(""), printed(""), None, printed(""), printed("str(\"new\")")]);
+    }
+}
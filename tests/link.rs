@@ -0,0 +1,69 @@
+//! Compile programs into executables with the `iranta` binary, which needs a system C compiler to link them.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Whether the C compiler used for linking can be run, as these tests cannot link anything without one.
+fn has_cc() -> bool {
+    let linker = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    Command::new(linker).arg("--version").output().is_ok_and(|output| output.status.success())
+}
+
+/// A directory of its own for the files of one test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("iranta-link-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn compile(source: &Path, output: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_iranta"))
+        .arg(source)
+        .arg("-o")
+        .arg(output)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn bundled_runtime_links() {
+    if !has_cc() {
+        eprintln!("Skipped: no C compiler to link with.");
+        return;
+    }
+    let dir = test_dir("bundled");
+    let source = dir.join("main.ir");
+    std::fs::write(&source, "let answer = 6 * 7;\n\"answer\";\nanswer\n").unwrap();
+    let exe = dir.join("main");
+
+    let output = compile(&source, &exe, &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let run = Command::new(exe.with_extension(std::env::consts::EXE_EXTENSION)).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "42\n");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn std_without_runtime_functions_is_rejected() {
+    if !has_cc() {
+        eprintln!("Skipped: no C compiler to link with.");
+        return;
+    }
+    let dir = test_dir("external");
+    let empty_std = dir.join("empty_std.o");
+    std::fs::write(dir.join("empty_std.c"), "int iranta_unrelated(void) { return 0; }\n").unwrap();
+    let linker = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(linker).arg("-c").arg(dir.join("empty_std.c")).arg("-o").arg(&empty_std).status().unwrap();
+    assert!(status.success());
+    let source = dir.join("main.ir");
+    std::fs::write(&source, "1 + 2\n").unwrap();
+
+    let output = compile(&source, &dir.join("main"), &[&format!("--std={}", empty_std.display())]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("must define std_print_int32, std_println."), "{}", stderr);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}